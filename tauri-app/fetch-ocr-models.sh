@@ -0,0 +1,79 @@
+#!/bin/bash
+# 🔎 Télécharge les modèles OCR (ocrs/rten) embarqués dans le bundle (src-tauri/resources/ocr)
+#
+# Usage :
+#   ./fetch-ocr-models.sh         # télécharge si besoin, vérifie contre SHA256SUMS
+#   ./fetch-ocr-models.sh --pin   # (mainteneurs) enregistre les empreintes dans SHA256SUMS
+
+set -euo pipefail
+
+BASE_URL="https://ocrs-models.s3-accelerate.amazonaws.com"
+MODELS=("text-detection.rten" "text-recognition.rten")
+DEST="$(cd "$(dirname "$0")" && pwd)/src-tauri/resources/ocr"
+SUMS="$DEST/SHA256SUMS"
+
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+NC='\033[0m' # No Color
+
+print_status() {
+    echo -e "${GREEN}✅ $1${NC}"
+}
+
+print_error() {
+    echo -e "${RED}❌ $1${NC}"
+}
+
+sha256() {
+    if command -v sha256sum > /dev/null; then
+        sha256sum "$1" | cut -d ' ' -f 1
+    else
+        shasum -a 256 "$1" | cut -d ' ' -f 1
+    fi
+}
+
+# Empreinte attendue d'un modèle, vide si elle n'est pas enregistrée
+expected_sum() {
+    [ -f "$SUMS" ] && awk -v name="$1" '$2 == name { print $1 }' "$SUMS" || true
+}
+
+PIN=false
+if [ "${1:-}" = "--pin" ]; then
+    PIN=true
+fi
+
+mkdir -p "$DEST"
+
+for model in "${MODELS[@]}"; do
+    target="$DEST/$model"
+    expected="$(expected_sum "$model")"
+
+    if [ -f "$target" ] && [ -n "$expected" ] && [ "$(sha256 "$target")" = "$expected" ]; then
+        print_status "$model déjà présent"
+        continue
+    fi
+
+    if [ -z "$expected" ] && [ "$PIN" = false ]; then
+        print_error "Aucune empreinte pour $model dans $SUMS, lancer ./fetch-ocr-models.sh --pin et committer SHA256SUMS"
+        exit 1
+    fi
+
+    echo "⬇️  Téléchargement de $model..."
+    tmp="$(mktemp "$DEST/.$model.XXXXXX")"
+    trap 'rm -f "$tmp"' EXIT
+    curl --fail --location --silent --show-error --proto '=https' --tlsv1.2 "$BASE_URL/$model" --output "$tmp"
+
+    actual="$(sha256 "$tmp")"
+    if [ "$PIN" = true ]; then
+        { grep -v " $model\$" "$SUMS" 2> /dev/null || true; echo "$actual $model"; } > "$SUMS.new"
+        mv "$SUMS.new" "$SUMS"
+        print_status "Empreinte de $model enregistrée : $actual"
+    elif [ "$actual" != "$expected" ]; then
+        print_error "Empreinte invalide pour $model : $actual (attendu $expected)"
+        exit 1
+    fi
+
+    mv "$tmp" "$target"
+    trap - EXIT
+    print_status "$model installé dans $DEST"
+done
@@ -0,0 +1,20 @@
+//! The parser never panics, and chunk boundaries do not change the events
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tauri_app_lib::fuzzing::SseParser;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&split, stream)) = data.split_first() else {
+        return;
+    };
+
+    let whole = SseParser::new().push(stream);
+
+    let split = split as usize % (stream.len() + 1);
+    let mut parser = SseParser::new();
+    let mut chunked = parser.push(&stream[..split]);
+    chunked.extend(parser.push(&stream[split..]));
+
+    assert_eq!(whole, chunked);
+});
@@ -0,0 +1,58 @@
+//! Validators never panic, and accepted inputs respect the documented bounds
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tauri_app_lib::fuzzing::*;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&selector, rest)) = data.split_first() else {
+        return;
+    };
+    let text = String::from_utf8_lossy(rest).into_owned();
+
+    match selector % 6 {
+        0 => {
+            let key = SecureKey { key: text };
+            if key.validate().is_ok() {
+                assert!(!key.key.is_empty() && key.key.len() <= 64);
+                assert!(key.key.is_ascii() && !key.key.contains(".."));
+            }
+        }
+        1 => {
+            // Secrets are opaque: only the size decides, whatever the content
+            let kv = SecureKeyValue { key: "token".to_string(), value: text };
+            assert_eq!(kv.validate().is_ok(), kv.value.len() <= MAX_SECRET_BYTES);
+        }
+        2 => {
+            let request = ChatRequest { message: text, conversation_id: None, context: None };
+            if request.validate().is_ok() {
+                assert!(!request.message.trim().is_empty() && request.message.len() <= 4000);
+            }
+        }
+        3 => {
+            let path = std::path::Path::new(&text);
+            if absolute_path("path", &text).is_ok() {
+                assert!(path.is_absolute());
+                assert!(path.components().all(|c| c != std::path::Component::ParentDir));
+            }
+        }
+        4 => {
+            if rest.len() >= 16 {
+                let width = f64::from_le_bytes(rest[..8].try_into().unwrap());
+                let height = f64::from_le_bytes(rest[8..16].try_into().unwrap());
+                let size = WindowSize { width, height };
+                if size.validate().is_ok() {
+                    assert!(width.is_finite() && height.is_finite());
+                }
+            }
+        }
+        _ => {
+            // Raw IPC payloads, as the invoke hook receives them
+            if let Ok(args) = serde_json::from_slice::<serde_json::Value>(rest) {
+                for command in ipc_commands() {
+                    let _ = validate_ipc_args(command, &args);
+                }
+            }
+        }
+    }
+});
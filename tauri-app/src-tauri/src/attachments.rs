@@ -0,0 +1,475 @@
+// src-tauri/src/attachments.rs
+//! 📎 File attachments for chat
+//!
+//! Features:
+//! - Files dropped on the input window (Tauri drag-and-drop events) or picked
+//! - Size and type validation (plain text, source code, PDF)
+//! - Text extraction, chunked to fit the context budget
+//! - Context block that asks the model to cite file names
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+
+/// Size limits (bytes)
+const MAX_TEXT_FILE_SIZE: u64 = 2 * 1024 * 1024;
+const MAX_PDF_FILE_SIZE: u64 = 20 * 1024 * 1024;
+
+/// Pending attachments per chat
+pub(crate) const MAX_ATTACHMENTS: usize = 5;
+
+/// Characters per chunk, and most bytes attachments may add to the context
+const CHUNK_CHARS: usize = 2000;
+pub const ATTACHMENT_BUDGET_BYTES: usize = 11000;
+
+/// Between the screen context and the attachments block
+const CONTEXT_SEPARATOR: &str = "\n\n";
+
+/// `.env` files are left out on purpose: they hold secrets the redactor can't all recognize
+const TEXT_EXTENSIONS: &[&str] = &[
+    "txt", "log", "md", "csv", "tsv", "json", "yaml", "yml", "toml", "xml", "ini", "conf", "cfg",
+];
+
+const CODE_EXTENSIONS: &[&str] = &[
+    "rs", "ts", "tsx", "js", "jsx", "mjs", "py", "go", "java", "kt", "swift", "c", "h", "cpp", "hpp",
+    "cc", "cs", "rb", "php", "sh", "zsh", "bash", "sql", "html", "css", "scss", "vue", "svelte", "lua",
+];
+
+/// Type of attached file
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AttachmentKind {
+    Text,
+    Code { language: String },
+    Pdf,
+}
+
+impl AttachmentKind {
+    /// Detect from the extension, `None` for unsupported files
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+
+        if extension == "pdf" {
+            Some(AttachmentKind::Pdf)
+        } else if CODE_EXTENSIONS.contains(&extension.as_str()) {
+            Some(AttachmentKind::Code { language: extension })
+        } else if TEXT_EXTENSIONS.contains(&extension.as_str()) {
+            Some(AttachmentKind::Text)
+        } else {
+            None
+        }
+    }
+
+    fn max_size(&self) -> u64 {
+        match self {
+            AttachmentKind::Pdf => MAX_PDF_FILE_SIZE,
+            _ => MAX_TEXT_FILE_SIZE,
+        }
+    }
+}
+
+/// Extracted file, split in chunks
+#[derive(Serialize, Debug, Clone)]
+pub struct Attachment {
+    pub id: String,
+    pub file_name: String,
+    pub path: PathBuf,
+    pub kind: AttachmentKind,
+    pub size: u64,
+    pub chunks: Vec<String>,
+}
+
+impl Attachment {
+    pub fn char_count(&self) -> usize {
+        self.chunks.iter().map(|c| c.chars().count()).sum()
+    }
+}
+
+/// Validate and extract one file
+pub fn load_attachment(path: &Path) -> Result<Attachment, String> {
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| "Invalid file name".to_string())?
+        .to_string();
+
+    let metadata = std::fs::metadata(path).map_err(|e| format!("{}: {}", file_name, e))?;
+    if !metadata.is_file() {
+        return Err(format!("{}: not a file", file_name));
+    }
+
+    let kind = AttachmentKind::from_path(path)
+        .ok_or_else(|| format!("{}: unsupported file type", file_name))?;
+
+    if metadata.len() > kind.max_size() {
+        return Err(format!("{}: file too large ({} bytes, max {})", file_name, metadata.len(), kind.max_size()));
+    }
+
+    let bytes = std::fs::read(path).map_err(|e| format!("{}: {}", file_name, e))?;
+    let text = match kind {
+        AttachmentKind::Pdf => extract_pdf_text(&bytes).map_err(|e| format!("{}: {}", file_name, e))?,
+        _ => decode_text(&bytes).ok_or_else(|| format!("{}: binary content is not supported", file_name))?,
+    };
+
+    if text.trim().is_empty() {
+        return Err(format!("{}: no text found", file_name));
+    }
+
+    Ok(Attachment {
+        id: uuid::Uuid::new_v4().to_string(),
+        file_name,
+        path: path.to_path_buf(),
+        kind,
+        size: metadata.len(),
+        chunks: chunk_text(&text, CHUNK_CHARS),
+    })
+}
+
+/// UTF-8 text, rejecting binary data (NUL bytes)
+fn decode_text(bytes: &[u8]) -> Option<String> {
+    if bytes.iter().take(8192).any(|b| *b == 0) {
+        return None;
+    }
+    Some(String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// PDF text extraction, panics in the parser on malformed files become errors
+fn extract_pdf_text(bytes: &[u8]) -> Result<String, String> {
+    std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem(bytes))
+        .map_err(|_| "malformed PDF".to_string())?
+        .map_err(|e| format!("PDF extraction failed: {}", e))
+}
+
+/// Split on line boundaries, lines longer than a chunk are split on chars
+pub fn chunk_text(text: &str, chunk_chars: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_len = 0;
+
+    for line in text.lines() {
+        let mut line: Vec<char> = line.chars().collect();
+
+        while !line.is_empty() {
+            let room = chunk_chars.saturating_sub(current_len + usize::from(current_len > 0));
+            if line.len() > room && current_len > 0 {
+                chunks.push(std::mem::take(&mut current));
+                current_len = 0;
+                continue;
+            }
+
+            let take = line.len().min(chunk_chars);
+            if current_len > 0 {
+                current.push('\n');
+                current_len += 1;
+            }
+            current.extend(line.drain(..take));
+            current_len += take;
+        }
+    }
+
+    if current_len > 0 {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Context block for `ChatRequest.context`, at most `budget` bytes (the unit of the `len`
+/// validation rule) shared evenly between files
+///
+/// Fails when the budget can't hold every file name, files are never dropped silently
+pub fn build_context(attachments: &[Attachment], budget: usize) -> Result<Option<String>, String> {
+    if attachments.is_empty() {
+        return Ok(None);
+    }
+
+    let mut context = String::from(
+        "Attached files. When you use information from a file, cite its name in brackets, e.g. [notes.txt].",
+    );
+    let per_file = budget.saturating_sub(context.len()) / attachments.len();
+
+    for attachment in attachments {
+        let total = attachment.chunks.len();
+        let header = format!("\n\n--- file: {} ---\n", attachment.file_name);
+        let note = |included: usize| format!("\n[... truncated: {} of {} parts included]", included, total);
+
+        // Room for the parts once the header and the longest truncation note are paid for
+        let room = per_file
+            .checked_sub(header.len() + note(total).len())
+            .ok_or_else(|| format!("No room left in the context for {}", attachment.file_name))?;
+
+        let mut used = 0;
+        let mut included = 0;
+        for chunk in &attachment.chunks {
+            let len = chunk.len() + usize::from(included > 0);
+            if used + len > room {
+                break;
+            }
+            used += len;
+            included += 1;
+        }
+
+        context.push_str(&header);
+        context.push_str(&attachment.chunks[..included].join("\n"));
+        if included < total {
+            context.push_str(&note(included));
+        }
+    }
+
+    Ok(Some(context))
+}
+
+/// Bytes left for attachments once `screen` context is in the request
+pub fn budget_after(screen: Option<&str>) -> usize {
+    let used = screen.map_or(0, |screen| screen.len() + CONTEXT_SEPARATOR.len());
+    crate::openai::MAX_CONTEXT_CHARS.saturating_sub(used).min(ATTACHMENT_BUDGET_BYTES)
+}
+
+/// Screen context followed by the attachments block
+pub fn join_context(screen: Option<String>, files: Option<String>) -> Option<String> {
+    match (screen, files) {
+        (Some(screen), Some(files)) => Some(format!("{}{}{}", screen, CONTEXT_SEPARATOR, files)),
+        (screen, files) => screen.or(files),
+    }
+}
+
+/// Summary sent to the frontend (no file contents)
+#[derive(Serialize, Debug, Clone)]
+pub struct AttachmentSummary {
+    pub id: String,
+    pub file_name: String,
+    pub kind: AttachmentKind,
+    pub size: u64,
+    pub chars: usize,
+}
+
+impl From<&Attachment> for AttachmentSummary {
+    fn from(attachment: &Attachment) -> Self {
+        Self {
+            id: attachment.id.clone(),
+            file_name: attachment.file_name.clone(),
+            kind: attachment.kind.clone(),
+            size: attachment.size,
+            chars: attachment.char_count(),
+        }
+    }
+}
+
+/// Outcome of adding files
+#[derive(Serialize, Debug, Clone)]
+pub struct AttachResult {
+    pub attached: Vec<AttachmentSummary>,
+    pub errors: Vec<String>,
+}
+
+/// Attachments waiting for the next chat message
+#[derive(Default)]
+pub struct AttachmentState(Mutex<Vec<Attachment>>);
+
+impl AttachmentState {
+    pub fn add(&self, paths: &[PathBuf]) -> AttachResult {
+        let mut result = AttachResult { attached: Vec::new(), errors: Vec::new() };
+
+        for path in paths {
+            if self.0.lock().unwrap().len() >= MAX_ATTACHMENTS {
+                result.errors.push(format!("Maximum {} attachments per message", MAX_ATTACHMENTS));
+                break;
+            }
+
+            match load_attachment(path) {
+                Ok(attachment) => {
+                    info!("📎 Attached {} ({} chunks)", attachment.file_name, attachment.chunks.len());
+                    result.attached.push(AttachmentSummary::from(&attachment));
+                    self.0.lock().unwrap().push(attachment);
+                }
+                Err(e) => {
+                    warn!("📎 Attachment rejected: {}", e);
+                    result.errors.push(e);
+                }
+            }
+        }
+
+        result
+    }
+
+    pub fn list(&self) -> Vec<AttachmentSummary> {
+        self.0.lock().unwrap().iter().map(AttachmentSummary::from).collect()
+    }
+
+    pub fn remove(&self, id: &str) -> bool {
+        let mut attachments = self.0.lock().unwrap();
+        let before = attachments.len();
+        attachments.retain(|a| a.id != id);
+        attachments.len() != before
+    }
+
+    /// Context block of the pending attachments within `budget` bytes, with the ids to pass to
+    /// `remove_sent` once the request is accepted
+    pub fn pending_context(&self, budget: usize) -> Result<(Vec<String>, Option<String>), String> {
+        let attachments = self.0.lock().unwrap();
+        let ids = attachments.iter().map(|a| a.id.clone()).collect();
+        Ok((ids, build_context(&attachments, budget)?))
+    }
+
+    /// Drop attachments sent with an accepted request, files added meanwhile stay pending
+    pub fn remove_sent(&self, ids: &[String]) {
+        self.0.lock().unwrap().retain(|a| !ids.contains(&a.id));
+    }
+}
+
+/// Handle files dropped on the input window
+pub fn handle_drop(app: &AppHandle, paths: Vec<PathBuf>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = app.state::<AttachmentState>().add(&paths);
+        let _ = app.emit_to("input", "attachments:changed", &result);
+    });
+}
+
+/// Attach files picked by the user
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+pub async fn add_attachments(app: AppHandle, paths: Vec<String>) -> Result<AttachResult, String> {
+    let paths: Vec<PathBuf> = paths.into_iter().map(PathBuf::from).collect();
+    tauri::async_runtime::spawn_blocking(move || app.state::<AttachmentState>().add(&paths))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all)]
+pub fn list_attachments(state: tauri::State<'_, AttachmentState>) -> Vec<AttachmentSummary> {
+    state.list()
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+pub fn remove_attachment(state: tauri::State<'_, AttachmentState>, id: String) -> Result<(), String> {
+    if state.remove(&id) {
+        Ok(())
+    } else {
+        Err(format!("Attachment not found: {}", id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/attachments").join(name)
+    }
+
+    #[test]
+    fn test_kind_detection() {
+        assert_eq!(AttachmentKind::from_path(Path::new("a/server.LOG")), Some(AttachmentKind::Text));
+        assert_eq!(AttachmentKind::from_path(Path::new("main.rs")), Some(AttachmentKind::Code { language: "rs".into() }));
+        assert_eq!(AttachmentKind::from_path(Path::new("report.pdf")), Some(AttachmentKind::Pdf));
+        assert_eq!(AttachmentKind::from_path(Path::new("photo.png")), None);
+        assert_eq!(AttachmentKind::from_path(Path::new("Makefile")), None);
+        assert_eq!(AttachmentKind::from_path(Path::new("prod.env")), None);
+    }
+
+    #[test]
+    fn test_load_text_code_and_pdf() {
+        let log = load_attachment(&fixture("server.log")).unwrap();
+        assert!(log.chunks[0].contains("connection pool exhausted"));
+
+        let code = load_attachment(&fixture("parser.rs")).unwrap();
+        assert_eq!(code.kind, AttachmentKind::Code { language: "rs".into() });
+
+        let pdf = load_attachment(&fixture("report.pdf")).unwrap();
+        assert!(pdf.chunks.join(" ").contains("Quarterly revenue grew"));
+
+        let binary = load_attachment(&fixture("binary.txt")).unwrap_err();
+        assert!(binary.contains("binary"));
+        assert!(load_attachment(&fixture("missing.txt")).is_err());
+    }
+
+    #[test]
+    fn test_oversized_file_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("huge.log");
+        std::fs::write(&path, "x".repeat(MAX_TEXT_FILE_SIZE as usize + 1)).unwrap();
+
+        let err = load_attachment(&path).unwrap_err();
+        assert!(err.contains("too large"));
+    }
+
+    #[test]
+    fn test_chunking_keeps_lines_and_limits() {
+        let text = (0..100).map(|i| format!("line {:03}", i)).collect::<Vec<_>>().join("\n");
+        let chunks = chunk_text(&text, 100);
+
+        assert!(chunks.iter().all(|c| c.chars().count() <= 100));
+        assert_eq!(chunks.join("\n"), text);
+
+        // A single line longer than a chunk is split
+        let long = chunk_text(&"y".repeat(250), 100);
+        assert_eq!(long.iter().map(|c| c.len()).collect::<Vec<_>>(), vec![100, 100, 50]);
+    }
+
+    #[test]
+    fn test_context_fits_budget_and_cites_names() {
+        let state = AttachmentState::default();
+        let result = state.add(&[fixture("server.log"), fixture("parser.rs"), fixture("photo.png")]);
+        assert_eq!(result.attached.len(), 2);
+        assert_eq!(result.errors.len(), 1);
+
+        let (ids, context) = state.pending_context(ATTACHMENT_BUDGET_BYTES).unwrap();
+        let context = context.unwrap();
+        assert!(context.contains("cite its name"));
+        assert!(context.contains("--- file: server.log ---"));
+        assert!(context.contains("--- file: parser.rs ---"));
+        state.remove_sent(&ids);
+        assert!(state.list().is_empty());
+
+        // Large files are truncated to the budget
+        let context = build_context(&[big_attachment("z")], ATTACHMENT_BUDGET_BYTES).unwrap().unwrap();
+        assert!(context.len() <= ATTACHMENT_BUDGET_BYTES);
+        assert!(context.contains("truncated"));
+    }
+
+    fn big_attachment(filler: &str) -> Attachment {
+        Attachment {
+            id: uuid::Uuid::new_v4().to_string(),
+            file_name: "big.log".into(),
+            path: PathBuf::from("big.log"),
+            kind: AttachmentKind::Text,
+            size: 0,
+            chunks: chunk_text(&filler.repeat(50_000), CHUNK_CHARS),
+        }
+    }
+
+    #[test]
+    fn test_full_screen_context_and_attachments_pass_validation() {
+        use crate::openai::{ChatRequest, MAX_CONTEXT_CHARS};
+        use crate::validation::ValidatedInput;
+
+        let request = |context: Option<String>| ChatRequest {
+            message: "Summarize".into(),
+            conversation_id: None,
+            context,
+        };
+
+        // Non-ASCII attachments: 2 to 4 bytes per character
+        let state = AttachmentState::default();
+        state.0.lock().unwrap().extend([big_attachment("é"), big_attachment("日"), big_attachment("🦀")]);
+
+        for screen_len in [0, 4000, MAX_CONTEXT_CHARS - 3000] {
+            let screen = (screen_len > 0).then(|| "ü".repeat(screen_len / 2));
+            let (ids, files) = state.pending_context(budget_after(screen.as_deref())).unwrap();
+            assert_eq!(ids.len(), 3);
+
+            let context = join_context(screen, files);
+            assert!(context.as_ref().unwrap().contains("--- file: big.log ---"));
+            assert!(request(context).validate().is_ok(), "screen context of {} bytes", screen_len);
+        }
+
+        // A full screen context leaves no room: the chat fails and the files stay pending
+        let full = "x".repeat(MAX_CONTEXT_CHARS);
+        assert!(state.pending_context(budget_after(Some(&full))).is_err());
+        assert_eq!(state.list().len(), 3);
+    }
+}
@@ -0,0 +1,729 @@
+// src-tauri/src/audit.rs
+//! 🧾 Append-only audit log of sensitive IPC commands
+//!
+//! Features:
+//! - One JSON line per call: command, calling window, timestamp, middleware outcome, redacted arguments
+//! - The outcome is the middleware decision, taken before the command runs: `admitted` does not
+//!   mean the command succeeded. A `completed` entry follows with its result (ok or error, never
+//!   the returned value), written when the command's `ipc` span closes
+//! - Permission denials are recorded for every command, not only sensitive ones
+//! - Hash chain (SHA-256 of the previous entry) so edits and deletions are detectable
+//! - Size-based rotation, the chain continues across rotated files
+//! - Written and synced to disk by the `audit` thread, the IPC dispatcher only queues entries
+//! - `get_audit_log` command with filters and an integrity report
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::JoinHandle;
+use tracing::span::{Attributes, Id};
+use tracing::{info, warn, debug, Event, Subscriber};
+use tracing_subscriber::filter::{filter_fn, Filtered};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+use crate::validation::ValidatedInput;
+
+const LOG_FILE: &str = "audit.jsonl";
+
+/// Rotation: current file size limit and number of files kept (current included)
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+const MAX_FILES: usize = 5;
+
+const DEFAULT_QUERY_LIMIT: usize = 200;
+pub(crate) const MAX_QUERY_LIMIT: usize = 5000;
+
+/// `prev_hash` of the very first entry
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Audited commands and the arguments kept in clear, every other argument is redacted
+const SENSITIVE_COMMANDS: &[(&str, &[&str])] = &[
+    ("secure_store", &["key"]),
+    ("secure_load", &["key"]),
+    ("secure_delete", &["key"]),
+    ("store_openai_key", &[]),
+    ("capture_screen", &[]),
+    ("capture_and_analyze", &[]),
+    ("capture_window", &["windowId"]),
+    ("get_image_as_base64", &["imagePath"]),
+    ("ocr_capture", &["imagePath"]),
+    ("redact_capture", &["imagePath", "provider"]),
+    ("toggle_stealth_cmd", &[]),
+    ("test_stealth_manual", &[]),
+    ("read_clipboard_context", &[]),
+    ("set_clipboard_permission", &["allowed"]),
+    ("add_attachments", &["paths"]),
+    ("attach_capture_to_chat", &["id"]),
+    ("delete_capture", &["id"]),
+    ("get_audit_log", &["filter"]),
+    ("export_crash_report", &["id"]),
+    ("set_consent", &["consent"]),
+    ("revoke_consent", &[]),
+    ("set_log_level", &["filter", "output"]),
+    ("subscribe_logs", &["filter"]),
+    ("export_diagnostics", &[]),
+];
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Result of the IPC middleware checks
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum AuditOutcome {
+    /// Passed the permission, rate limit and argument checks and handed to the command,
+    /// whatever the command then returns
+    Admitted,
+    /// The calling window may not use the command (`permissions.rs`)
+    Denied { reason: String },
+    /// Rate limit or argument validation failed
+    Rejected { reason: String },
+    /// An admitted call returned, follows its `Admitted` entry
+    Completed { result: CallResult },
+}
+
+/// What an admitted command returned, without the value itself
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CallResult {
+    Ok,
+    Error,
+}
+
+/// One audited call
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    pub command: String,
+    pub window: String,
+    pub outcome: AuditOutcome,
+    pub args: Value,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+impl AuditEntry {
+    /// SHA-256 of every field but `hash`
+    fn compute_hash(&self) -> String {
+        let content = json!({
+            "seq": self.seq,
+            "timestamp": self.timestamp,
+            "command": self.command,
+            "window": self.window,
+            "outcome": self.outcome,
+            "args": self.args,
+            "prev_hash": self.prev_hash,
+        });
+        format!("{:x}", Sha256::digest(content.to_string().as_bytes()))
+    }
+}
+
+/// Chain check result
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Integrity {
+    pub intact: bool,
+    /// `seq` of the first entry whose hash or link does not match
+    pub broken_at: Option<u64>,
+    /// Lines that are not valid entries
+    pub unreadable_lines: usize,
+}
+
+/// Check hashes and links, the first entry read anchors the chain (older files may be rotated out)
+pub fn verify_chain(entries: &[AuditEntry]) -> Result<(), u64> {
+    let mut previous: Option<&AuditEntry> = None;
+
+    for entry in entries {
+        if entry.hash != entry.compute_hash() {
+            return Err(entry.seq);
+        }
+        if let Some(previous) = previous {
+            if entry.prev_hash != previous.hash || entry.seq != previous.seq + 1 {
+                return Err(entry.seq);
+            }
+        }
+        previous = Some(entry);
+    }
+    Ok(())
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutcomeFilter {
+    Admitted,
+    Denied,
+    Rejected,
+    Completed,
+}
+
+/// Filters of `get_audit_log`, all optional
+#[derive(Deserialize, Debug, Clone, Default, ValidatedInput)]
+pub struct AuditFilter {
+    #[validate(non_empty, len(max = 64), charset = "alnum_")]
+    pub command: Option<String>,
+    #[validate(non_empty, len(max = 64), charset = "alnum_-")]
+    pub window: Option<String>,
+    pub outcome: Option<OutcomeFilter>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    #[validate(range(1..=MAX_QUERY_LIMIT))]
+    pub limit: Option<usize>,
+}
+
+impl AuditFilter {
+    fn matches(&self, entry: &AuditEntry) -> bool {
+        self.command.as_ref().is_none_or(|c| c == &entry.command)
+            && self.window.as_ref().is_none_or(|w| w == &entry.window)
+            && self.outcome.is_none_or(|o| match o {
+                OutcomeFilter::Admitted => entry.outcome == AuditOutcome::Admitted,
+                OutcomeFilter::Denied => matches!(entry.outcome, AuditOutcome::Denied { .. }),
+                OutcomeFilter::Rejected => matches!(entry.outcome, AuditOutcome::Rejected { .. }),
+                OutcomeFilter::Completed => matches!(entry.outcome, AuditOutcome::Completed { .. }),
+            })
+            && self.since.is_none_or(|since| entry.timestamp >= since)
+            && self.until.is_none_or(|until| entry.timestamp <= until)
+    }
+}
+
+/// Filtered entries, newest first, with the integrity of the whole log
+#[derive(Serialize, Debug, Clone)]
+pub struct AuditLogPage {
+    pub entries: Vec<AuditEntry>,
+    pub total_matches: usize,
+    pub integrity: Integrity,
+}
+
+/// Log files in one directory: `audit.jsonl` (current), `audit.1.jsonl` (previous)...
+pub struct AuditLog {
+    dir: PathBuf,
+    max_file_bytes: u64,
+    max_files: usize,
+    /// `seq` and hash of the last entry written
+    last: Option<(u64, String)>,
+}
+
+impl AuditLog {
+    /// Open the log and resume its chain
+    pub fn open(dir: &Path) -> Result<Self, String> {
+        fs::create_dir_all(dir).map_err(|e| format!("Cannot create audit directory: {}", e))?;
+
+        let mut log = Self {
+            dir: dir.to_path_buf(),
+            max_file_bytes: MAX_FILE_BYTES,
+            max_files: MAX_FILES,
+            last: None,
+        };
+        let (entries, _) = log.read_all();
+        log.last = entries.last().map(|entry| (entry.seq, entry.hash.clone()));
+        Ok(log)
+    }
+
+    pub fn with_rotation(mut self, max_file_bytes: u64, max_files: usize) -> Self {
+        self.max_file_bytes = max_file_bytes;
+        self.max_files = max_files.max(1);
+        self
+    }
+
+    fn file(&self, index: usize) -> PathBuf {
+        match index {
+            0 => self.dir.join(LOG_FILE),
+            n => self.dir.join(format!("audit.{}.jsonl", n)),
+        }
+    }
+
+    /// Append one entry chained to the previous one
+    pub fn append(&mut self, command: &str, window: &str, outcome: AuditOutcome, args: Value) -> Result<AuditEntry, String> {
+        self.append_at(Utc::now(), command, window, outcome, args)
+    }
+
+    /// Same, for a call made at `timestamp`
+    fn append_at(
+        &mut self,
+        timestamp: DateTime<Utc>,
+        command: &str,
+        window: &str,
+        outcome: AuditOutcome,
+        args: Value,
+    ) -> Result<AuditEntry, String> {
+        let (seq, prev_hash) = match &self.last {
+            Some((seq, hash)) => (seq + 1, hash.clone()),
+            None => (0, GENESIS_HASH.to_string()),
+        };
+
+        let mut entry = AuditEntry {
+            seq,
+            timestamp,
+            command: command.to_string(),
+            window: window.to_string(),
+            outcome,
+            args,
+            prev_hash,
+            hash: String::new(),
+        };
+        entry.hash = entry.compute_hash();
+
+        let line = serde_json::to_string(&entry).map_err(|e| e.to_string())? + "\n";
+        self.rotate_if_needed(line.len() as u64)?;
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.file(0))
+            .map_err(|e| format!("Cannot open audit log: {}", e))?;
+        file.write_all(line.as_bytes()).map_err(|e| format!("Cannot write audit log: {}", e))?;
+        file.sync_data().map_err(|e| e.to_string())?;
+
+        self.last = Some((entry.seq, entry.hash.clone()));
+        Ok(entry)
+    }
+
+    fn rotate_if_needed(&self, incoming: u64) -> Result<(), String> {
+        let size = fs::metadata(self.file(0)).map(|m| m.len()).unwrap_or(0);
+        if size == 0 || size + incoming <= self.max_file_bytes {
+            return Ok(());
+        }
+
+        // The oldest file is dropped, the others shift by one
+        let _ = fs::remove_file(self.file(self.max_files - 1));
+        for index in (0..self.max_files - 1).rev() {
+            let from = self.file(index);
+            if from.exists() {
+                fs::rename(&from, self.file(index + 1)).map_err(|e| format!("Audit rotation failed: {}", e))?;
+            }
+        }
+        debug!("🧾 Audit log rotated");
+        Ok(())
+    }
+
+    /// Every entry, oldest first, and the number of unreadable lines
+    fn read_all(&self) -> (Vec<AuditEntry>, usize) {
+        let mut entries = Vec::new();
+        let mut unreadable = 0;
+
+        for index in (0..self.max_files).rev() {
+            let Ok(content) = fs::read_to_string(self.file(index)) else {
+                continue;
+            };
+            for line in content.lines().filter(|l| !l.trim().is_empty()) {
+                match serde_json::from_str::<AuditEntry>(line) {
+                    Ok(entry) => entries.push(entry),
+                    Err(_) => unreadable += 1,
+                }
+            }
+        }
+        (entries, unreadable)
+    }
+
+    pub fn query(&self, filter: &AuditFilter) -> AuditLogPage {
+        let (entries, unreadable_lines) = self.read_all();
+        let broken_at = verify_chain(&entries).err();
+
+        let matching: Vec<AuditEntry> = entries.into_iter().rev().filter(|e| filter.matches(e)).collect();
+        let total_matches = matching.len();
+
+        AuditLogPage {
+            entries: matching.into_iter().take(filter.limit.unwrap_or(DEFAULT_QUERY_LIMIT)).collect(),
+            total_matches,
+            integrity: Integrity {
+                intact: broken_at.is_none() && unreadable_lines == 0,
+                broken_at,
+                unreadable_lines,
+            },
+        }
+    }
+}
+
+/// Arguments to keep in clear for `command`, `None` when it is not audited
+fn visible_args(command: &str) -> Option<&'static [&'static str]> {
+    SENSITIVE_COMMANDS.iter().find(|(name, _)| *name == command).map(|(_, args)| *args)
+}
+
+pub fn is_sensitive(command: &str) -> bool {
+    visible_args(command).is_some()
+}
+
+/// Sensitive commands and every permission denial are recorded
+pub fn should_record(command: &str, outcome: &AuditOutcome) -> bool {
+    is_sensitive(command) || matches!(outcome, AuditOutcome::Denied { .. })
+}
+
+/// Keep listed arguments, replace the others
+pub fn redact_args(visible: &[&str], args: &Value) -> Value {
+    match args {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(name, value)| {
+                    let value = if visible.contains(&name.as_str()) { value.clone() } else { json!(REDACTED) };
+                    (name.clone(), value)
+                })
+                .collect(),
+        ),
+        Value::Null => Value::Null,
+        _ => json!(REDACTED),
+    }
+}
+
+static AUDIT_LOG: Lazy<Arc<Mutex<Option<AuditLog>>>> = Lazy::new(|| Arc::new(Mutex::new(None)));
+
+/// Queue of the `audit` thread, set by `init`
+static WRITER: OnceCell<mpsc::Sender<PendingEntry>> = OnceCell::new();
+
+/// A call waiting to be written, arguments already redacted
+struct PendingEntry {
+    timestamp: DateTime<Utc>,
+    command: String,
+    window: String,
+    outcome: AuditOutcome,
+    args: Value,
+}
+
+/// Append queued entries in order, until every sender is dropped
+fn spawn_writer(log: Arc<Mutex<Option<AuditLog>>>) -> std::io::Result<(mpsc::Sender<PendingEntry>, JoinHandle<()>)> {
+    let (sender, receiver) = mpsc::channel::<PendingEntry>();
+    let handle = std::thread::Builder::new().name("audit".into()).spawn(move || {
+        for pending in receiver {
+            let mut guard = log.lock().unwrap();
+            let Some(log) = guard.as_mut() else {
+                continue;
+            };
+            let PendingEntry { timestamp, command, window, outcome, args } = pending;
+            if let Err(e) = log.append_at(timestamp, &command, &window, outcome, args) {
+                warn!("🧾 Audit entry for '{}' lost: {}", command, e);
+            }
+        }
+    })?;
+    Ok((sender, handle))
+}
+
+/// Open the log in `dir` and start its writer, called once at startup
+pub fn init(dir: &Path) {
+    match AuditLog::open(dir) {
+        Ok(log) => {
+            *AUDIT_LOG.lock().unwrap() = Some(log);
+            match spawn_writer(AUDIT_LOG.clone()) {
+                Ok((sender, _)) => {
+                    let _ = WRITER.set(sender);
+                    info!("🧾 Audit log ready in {}", dir.display());
+                }
+                Err(e) => warn!("🧾 Audit log disabled, writer not started: {}", e),
+            }
+        }
+        Err(e) => warn!("🧾 Audit log disabled: {}", e),
+    }
+}
+
+/// Record a call, see `should_record`; arguments of non-sensitive commands are all redacted.
+/// Queued for the `audit` thread: the caller never waits on the disk
+pub fn record(command: &str, window: &str, outcome: AuditOutcome, args: &Value) {
+    if !should_record(command, &outcome) {
+        return;
+    }
+    let Some(writer) = WRITER.get() else {
+        warn!("🧾 Audit log not initialized, '{}' not recorded", command);
+        return;
+    };
+
+    let admitted = outcome == AuditOutcome::Admitted;
+    let visible = visible_args(command).unwrap_or(&[]);
+    let pending = PendingEntry {
+        timestamp: Utc::now(),
+        command: command.to_string(),
+        window: window.to_string(),
+        outcome,
+        args: redact_args(visible, args),
+    };
+    if writer.send(pending).is_err() {
+        warn!("🧾 Audit writer stopped, '{}' not recorded", command);
+        return;
+    }
+    if admitted {
+        expect_completion(command, window);
+    }
+}
+
+/// Windows of the admitted calls whose command has not started yet, oldest first by command
+static AWAITING: Lazy<Mutex<HashMap<String, VecDeque<String>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn expect_completion(command: &str, window: &str) {
+    AWAITING.lock().unwrap().entry(command.to_string()).or_default().push_back(window.to_string());
+}
+
+/// Window of the oldest admitted call of `command` not started yet, `None` when the command
+/// is called from Rust rather than through IPC
+fn next_admitted(command: &str) -> Option<String> {
+    let mut awaiting = AWAITING.lock().unwrap();
+    let windows = awaiting.get_mut(command)?;
+    let window = windows.pop_front();
+    if windows.is_empty() {
+        awaiting.remove(command);
+    }
+    window
+}
+
+/// Admitted call running in an `ipc` span, kept in its extensions
+struct RunningCall {
+    window: String,
+    failed: bool,
+}
+
+/// Records the result of admitted sensitive commands when their `ipc` span closes
+pub struct AuditLayer;
+
+impl<S> Layer<S> for AuditLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let command = attrs.metadata().name();
+        if !is_sensitive(command) {
+            return;
+        }
+        let Some(span) = ctx.span(id) else { return };
+        if let Some(window) = next_admitted(command) {
+            span.extensions_mut().insert(RunningCall { window, failed: false });
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if !crate::metrics::is_error_event(event) {
+            return;
+        }
+        let Some(span) = ctx.event_span(event) else { return };
+        let mut extensions = span.extensions_mut();
+        if let Some(call) = extensions.get_mut::<RunningCall>() {
+            call.failed = true;
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else { return };
+        let Some(call) = span.extensions_mut().remove::<RunningCall>() else { return };
+
+        let result = if call.failed { CallResult::Error } else { CallResult::Ok };
+        record(span.name(), &call.window, AuditOutcome::Completed { result }, &Value::Null);
+    }
+}
+
+/// Audit layer, only sees the spans and events of IPC commands
+pub fn layer<S>() -> Filtered<AuditLayer, impl tracing_subscriber::layer::Filter<S>, S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    AuditLayer.with_filter(filter_fn(|meta| meta.target() == crate::metrics::IPC_TARGET))
+}
+
+/// Read the audit log, newest first
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+pub fn get_audit_log(filter: Option<AuditFilter>) -> Result<AuditLogPage, String> {
+    let guard = AUDIT_LOG.lock().unwrap();
+    let log = guard.as_ref().ok_or("Audit log not initialized")?;
+    Ok(log.query(&filter.unwrap_or_default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(log: &mut AuditLog, command: &str, window: &str) -> AuditEntry {
+        log.append(command, window, AuditOutcome::Admitted, json!({ "key": "api" })).unwrap()
+    }
+
+    #[test]
+    fn test_entries_are_chained_and_log_resumes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = AuditLog::open(dir.path()).unwrap();
+        let first = write(&mut log, "secure_load", "input");
+        let second = write(&mut log, "capture_screen", "hud");
+
+        assert_eq!(first.prev_hash, GENESIS_HASH);
+        assert_eq!(second.prev_hash, first.hash);
+
+        let mut reopened = AuditLog::open(dir.path()).unwrap();
+        let third = write(&mut reopened, "toggle_stealth_cmd", "hud");
+        assert_eq!((third.seq, third.prev_hash.as_str()), (2, second.hash.as_str()));
+
+        let page = reopened.query(&AuditFilter::default());
+        assert!(page.integrity.intact);
+        assert_eq!(page.entries[0].seq, 2);
+    }
+
+    #[test]
+    fn test_tampering_is_detected() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = AuditLog::open(dir.path()).unwrap();
+        for _ in 0..3 {
+            write(&mut log, "secure_load", "input");
+        }
+
+        // Edit the second entry in place
+        let path = dir.path().join(LOG_FILE);
+        let content = fs::read_to_string(&path).unwrap();
+        let mut lines: Vec<String> = content.lines().map(String::from).collect();
+        lines[1] = lines[1].replace("\"input\"", "\"hud\"");
+        fs::write(&path, lines.join("\n") + "\n").unwrap();
+        assert_eq!(log.query(&AuditFilter::default()).integrity.broken_at, Some(1));
+
+        // Remove it instead
+        lines = content.lines().map(String::from).collect();
+        lines.remove(1);
+        fs::write(&path, lines.join("\n") + "\n").unwrap();
+        assert_eq!(log.query(&AuditFilter::default()).integrity.broken_at, Some(2));
+
+        fs::write(&path, content + "garbage\n").unwrap();
+        let integrity = log.query(&AuditFilter::default()).integrity;
+        assert!(!integrity.intact);
+        assert_eq!(integrity.unreadable_lines, 1);
+    }
+
+    #[test]
+    fn test_rotation_keeps_the_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = AuditLog::open(dir.path()).unwrap().with_rotation(600, 3);
+        for _ in 0..20 {
+            write(&mut log, "capture_screen", "hud");
+        }
+
+        let files = fs::read_dir(dir.path()).unwrap().count();
+        assert_eq!(files, 3);
+
+        let page = log.query(&AuditFilter::default());
+        assert!(page.integrity.intact);
+        assert_eq!(page.entries[0].seq, 19);
+        assert!(page.entries.len() < 20);
+    }
+
+    #[test]
+    fn test_filters() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = AuditLog::open(dir.path()).unwrap();
+        write(&mut log, "secure_load", "input");
+        write(&mut log, "capture_screen", "hud");
+        let rejected = AuditOutcome::Rejected { reason: "Rate limit exceeded".into() };
+        log.append("secure_load", "context", rejected, json!({})).unwrap();
+
+        let by_command = AuditFilter { command: Some("secure_load".into()), ..Default::default() };
+        assert_eq!(log.query(&by_command).total_matches, 2);
+
+        let by_window = AuditFilter { window: Some("hud".into()), ..Default::default() };
+        assert_eq!(log.query(&by_window).entries[0].command, "capture_screen");
+
+        let rejected = AuditFilter { outcome: Some(OutcomeFilter::Rejected), ..Default::default() };
+        assert_eq!(log.query(&rejected).entries[0].window, "context");
+
+        let admitted = AuditFilter { outcome: Some(OutcomeFilter::Admitted), ..Default::default() };
+        let page = log.query(&admitted);
+        assert_eq!(page.total_matches, 2);
+        assert_eq!(serde_json::to_value(&page.entries[0].outcome).unwrap(), json!({ "status": "admitted" }));
+
+        let denied = AuditFilter { outcome: Some(OutcomeFilter::Denied), ..Default::default() };
+        assert_eq!(log.query(&denied).total_matches, 0);
+
+        let future = AuditFilter { since: Some(Utc::now() + chrono::Duration::hours(1)), ..Default::default() };
+        assert_eq!(log.query(&future).total_matches, 0);
+
+        let limited = AuditFilter { limit: Some(1), ..Default::default() };
+        let page = log.query(&limited);
+        assert_eq!((page.entries.len(), page.total_matches), (1, 3));
+    }
+
+    #[test]
+    fn test_writer_thread_appends_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = Arc::new(Mutex::new(Some(AuditLog::open(dir.path()).unwrap())));
+        let (sender, handle) = spawn_writer(log.clone()).unwrap();
+
+        let called_at = Utc::now() - chrono::Duration::seconds(5);
+        for command in ["secure_store", "secure_load", "secure_delete"] {
+            let pending = PendingEntry {
+                timestamp: called_at,
+                command: command.into(),
+                window: "panel".into(),
+                outcome: AuditOutcome::Admitted,
+                args: json!({ "key": "api" }),
+            };
+            sender.send(pending).unwrap();
+        }
+        drop(sender);
+        handle.join().unwrap();
+
+        let page = log.lock().unwrap().as_ref().unwrap().query(&AuditFilter::default());
+        assert!(page.integrity.intact);
+        let commands: Vec<_> = page.entries.iter().rev().map(|e| e.command.as_str()).collect();
+        assert_eq!(commands, vec!["secure_store", "secure_load", "secure_delete"]);
+        // Time of the call, not of the write
+        assert_eq!(page.entries[0].timestamp, called_at);
+    }
+
+    #[test]
+    fn test_completion_follows_admitted_call() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        #[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+        fn secure_delete(fail: bool) -> Result<(), String> {
+            if fail { Err("locked".to_string()) } else { Ok(()) }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        init(dir.path());
+        let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(layer()));
+
+        record("secure_delete", "panel", AuditOutcome::Admitted, &json!({ "key": "api" }));
+        secure_delete(false).unwrap();
+        record("secure_delete", "input", AuditOutcome::Admitted, &json!({ "key": "api" }));
+        secure_delete(true).unwrap_err();
+        // Called from Rust, not through the middleware: nothing to complete
+        secure_delete(false).unwrap();
+
+        let filter = AuditFilter { command: Some("secure_delete".into()), ..Default::default() };
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        let page = loop {
+            let page = get_audit_log(Some(filter.clone())).unwrap();
+            if page.total_matches >= 4 || std::time::Instant::now() > deadline {
+                break page;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        };
+
+        assert!(page.integrity.intact);
+        let calls: Vec<_> = page.entries.iter().rev().map(|e| (e.window.as_str(), e.outcome.clone())).collect();
+        assert_eq!(calls, vec![
+            ("panel", AuditOutcome::Admitted),
+            ("panel", AuditOutcome::Completed { result: CallResult::Ok }),
+            ("input", AuditOutcome::Admitted),
+            ("input", AuditOutcome::Completed { result: CallResult::Error }),
+        ]);
+        // The result only, never the returned value
+        assert_eq!(page.entries[0].args, Value::Null);
+        assert_eq!(
+            serde_json::to_value(&page.entries[0].outcome).unwrap(),
+            json!({ "status": "completed", "result": "error" })
+        );
+
+        let completed = AuditFilter { outcome: Some(OutcomeFilter::Completed), ..filter };
+        assert_eq!(get_audit_log(Some(completed)).unwrap().total_matches, 2);
+    }
+
+    #[test]
+    fn test_arguments_are_redacted() {
+        let visible = visible_args("secure_store").unwrap();
+        let args = json!({ "key": "openai_api_key", "value": "sk-secret" });
+        assert_eq!(redact_args(visible, &args), json!({ "key": "openai_api_key", "value": REDACTED }));
+
+        let key = json!({ "key": "sk-proj-secret" });
+        assert_eq!(redact_args(visible_args("store_openai_key").unwrap(), &key), json!({ "key": REDACTED }));
+
+        assert!(is_sensitive("get_image_as_base64"));
+        assert!(!is_sensitive("panel_show"));
+
+        let denied = AuditOutcome::Denied { reason: "Window 'input' is not allowed".into() };
+        assert!(should_record("panel_show", &denied));
+        assert!(!should_record("panel_show", &AuditOutcome::Admitted));
+    }
+}
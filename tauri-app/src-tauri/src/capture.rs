@@ -0,0 +1,346 @@
+// src-tauri/src/capture.rs
+//! 📸 Capture encoding options for Numa
+//!
+//! Features:
+//! - Output format selection (PNG, JPEG, WebP)
+//! - Quality control for JPEG (PNG and WebP are always lossless, a quality is refused for them)
+//! - Long-edge downscaling with high-quality resampling
+//! - Separate settings for saved captures and vision uploads
+
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::imageops::FilterType;
+use image::{ColorType, ImageEncoder, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tracing::{debug, info};
+use crate::validation::{validate_input, ValidatedInput, ValidationError};
+
+/// Bounds accepted for the long-edge limit
+const MIN_DIMENSION: u32 = 64;
+const MAX_DIMENSION: u32 = 8192;
+
+/// Default long edge for vision uploads (vision models downscale beyond this anyway)
+const DEFAULT_VISION_MAX_DIMENSION: u32 = 2048;
+
+/// JPEG quality when none is given
+const DEFAULT_JPEG_QUALITY: u8 = 90;
+
+/// Shared capture settings, read by every capture and upload
+static CAPTURE_SETTINGS: once_cell::sync::Lazy<Arc<Mutex<CaptureSettings>>> =
+    once_cell::sync::Lazy::new(|| Arc::new(Mutex::new(CaptureSettings::default())));
+
+/// Output image format
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptureFormat {
+    Png,
+    Jpeg,
+    Webp,
+}
+
+impl CaptureFormat {
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            CaptureFormat::Png => "image/png",
+            CaptureFormat::Jpeg => "image/jpeg",
+            CaptureFormat::Webp => "image/webp",
+        }
+    }
+
+    /// Only lossy formats use `CaptureOptions::quality`
+    pub fn is_lossy(&self) -> bool {
+        matches!(self, CaptureFormat::Jpeg)
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            CaptureFormat::Png => "png",
+            CaptureFormat::Jpeg => "jpg",
+            CaptureFormat::Webp => "webp",
+        }
+    }
+}
+
+/// Encoding options for one output
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ValidatedInput)]
+pub struct CaptureOptions {
+    pub format: CaptureFormat,
+    /// 1-100, JPEG only (`DEFAULT_JPEG_QUALITY` when `None`): WebP is encoded losslessly, the
+    /// lossy WebP encoder of `image` needs libwebp and is deprecated
+    #[serde(default)]
+    #[validate(range(1..=100))]
+    pub quality: Option<u8>,
+    /// Maximum size of the longest edge in pixels, `None` keeps the native resolution
+    #[validate(range(MIN_DIMENSION..=MAX_DIMENSION))]
+    pub max_dimension: Option<u32>,
+}
+
+impl Default for CaptureOptions {
+    fn default() -> Self {
+        Self {
+            format: CaptureFormat::Png,
+            quality: None,
+            max_dimension: None,
+        }
+    }
+}
+
+impl CaptureOptions {
+    /// Defaults used when an image is sent to a vision model
+    pub fn vision_default() -> Self {
+        Self {
+            format: CaptureFormat::Jpeg,
+            quality: Some(80),
+            max_dimension: Some(DEFAULT_VISION_MAX_DIMENSION),
+        }
+    }
+
+    /// Quality actually applied by `encode`, `None` for lossless formats
+    pub fn applied_quality(&self) -> Option<u8> {
+        self.format.is_lossy().then(|| self.quality.unwrap_or(DEFAULT_JPEG_QUALITY))
+    }
+}
+
+/// `custom` rule: a quality is only accepted with a lossy format, never silently ignored
+pub fn quality_matches_format(field: &str, value: &CaptureOptions) -> Result<(), ValidationError> {
+    if value.quality.is_some() && !value.format.is_lossy() {
+        return Err(ValidationError::InvalidFormat {
+            field: format!("{}.quality", field),
+            reason: format!("{} is lossless, quality only applies to jpeg", value.format.extension()),
+        });
+    }
+    Ok(())
+}
+
+/// Settings for saved captures and for the vision upload path
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ValidatedInput)]
+pub struct CaptureSettings {
+    #[validate(nested, custom = "quality_matches_format")]
+    pub capture: CaptureOptions,
+    #[validate(nested, custom = "quality_matches_format")]
+    pub vision: CaptureOptions,
+}
+
+impl Default for CaptureSettings {
+    fn default() -> Self {
+        Self {
+            capture: CaptureOptions::default(),
+            vision: CaptureOptions::vision_default(),
+        }
+    }
+}
+
+/// Result of encoding an image
+#[derive(Debug, Clone)]
+pub struct EncodedImage {
+    pub bytes: Vec<u8>,
+    pub format: CaptureFormat,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Current effective settings
+pub fn current_settings() -> CaptureSettings {
+    CAPTURE_SETTINGS.lock().unwrap().clone()
+}
+
+/// Downscale so that the longest edge fits `max_dimension` (never upscales)
+pub fn fit_to_max_dimension(image: &RgbaImage, max_dimension: Option<u32>) -> Option<RgbaImage> {
+    let max = max_dimension?;
+    let (width, height) = image.dimensions();
+    let long_edge = width.max(height);
+
+    if long_edge <= max {
+        return None;
+    }
+
+    let scale = max as f64 / long_edge as f64;
+    let new_width = ((width as f64 * scale).round() as u32).max(1);
+    let new_height = ((height as f64 * scale).round() as u32).max(1);
+
+    debug!("📐 Downscaling capture {}x{} -> {}x{}", width, height, new_width, new_height);
+    Some(image::imageops::resize(image, new_width, new_height, FilterType::Lanczos3))
+}
+
+/// Encode an RGBA image with the given options
+pub fn encode(image: &RgbaImage, options: &CaptureOptions) -> Result<EncodedImage, String> {
+    let resized = fit_to_max_dimension(image, options.max_dimension);
+    let image = resized.as_ref().unwrap_or(image);
+    let (width, height) = image.dimensions();
+
+    let mut bytes = Vec::new();
+    match options.format {
+        CaptureFormat::Png => {
+            PngEncoder::new(&mut bytes)
+                .write_image(image.as_raw(), width, height, ColorType::Rgba8)
+                .map_err(|e| format!("Erreur d'encodage PNG: {}", e))?;
+        }
+        CaptureFormat::Jpeg => {
+            // JPEG has no alpha channel
+            let rgb = image::DynamicImage::ImageRgba8(image.clone()).to_rgb8();
+            JpegEncoder::new_with_quality(&mut bytes, options.applied_quality().unwrap_or(DEFAULT_JPEG_QUALITY))
+                .encode(rgb.as_raw(), width, height, ColorType::Rgb8)
+                .map_err(|e| format!("Erreur d'encodage JPEG: {}", e))?;
+        }
+        CaptureFormat::Webp => {
+            // Lossless, `quality` is refused by validation
+            WebPEncoder::new_lossless(&mut bytes)
+                .encode(image.as_raw(), width, height, ColorType::Rgba8)
+                .map_err(|e| format!("Erreur d'encodage WebP: {}", e))?;
+        }
+    }
+
+    Ok(EncodedImage {
+        bytes,
+        format: options.format,
+        width,
+        height,
+    })
+}
+
+/// Encode an image with the vision upload settings
+pub fn encode_for_vision(image: &RgbaImage) -> Result<EncodedImage, String> {
+    encode(image, &current_settings().vision)
+}
+
+/// Write an encoded capture to the temporary screenshots directory
+pub fn save_to_temp(encoded: &EncodedImage, prefix: &str) -> Result<std::path::PathBuf, String> {
+    let screenshots_dir = std::env::temp_dir().join("tauri-screenshots");
+    std::fs::create_dir_all(&screenshots_dir).map_err(|e| e.to_string())?;
+
+    // Générer un nom de fichier unique
+    let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
+    let filename = format!("{}_{}.{}", prefix, timestamp, encoded.format.extension());
+    let file_path = screenshots_dir.join(filename);
+
+    std::fs::write(&file_path, &encoded.bytes).map_err(|e| e.to_string())?;
+    Ok(file_path)
+}
+
+/// Get current capture settings
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all)]
+pub fn get_capture_options() -> CaptureSettings {
+    current_settings()
+}
+
+/// Update capture settings
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+pub fn set_capture_options(settings: CaptureSettings) -> Result<CaptureSettings, String> {
+    validate_input("set_capture_options", settings, |validated| {
+        *CAPTURE_SETTINGS.lock().unwrap() = validated.clone();
+        info!("📸 Capture settings updated: capture={:?}, vision={:?}",
+              validated.capture.format, validated.vision.format);
+        Ok(validated)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradient(width: u32, height: u32) -> RgbaImage {
+        RgbaImage::from_fn(width, height, |x, y| {
+            image::Rgba([(x % 256) as u8, (y % 256) as u8, 128, 255])
+        })
+    }
+
+    #[test]
+    fn test_fit_to_max_dimension() {
+        let image = gradient(400, 200);
+
+        // Landscape: long edge is the width
+        let resized = fit_to_max_dimension(&image, Some(100)).unwrap();
+        assert_eq!(resized.dimensions(), (100, 50));
+
+        // Never upscales
+        assert!(fit_to_max_dimension(&image, Some(1000)).is_none());
+        assert!(fit_to_max_dimension(&image, None).is_none());
+    }
+
+    #[test]
+    fn test_encode_formats() {
+        let image = gradient(64, 48);
+
+        for format in [CaptureFormat::Png, CaptureFormat::Jpeg, CaptureFormat::Webp] {
+            let options = CaptureOptions { format, quality: None, max_dimension: None };
+            let encoded = encode(&image, &options).unwrap();
+            assert_eq!(encoded.format, format);
+            assert_eq!((encoded.width, encoded.height), (64, 48));
+
+            let decoded = image::load_from_memory(&encoded.bytes).unwrap();
+            assert_eq!((decoded.width(), decoded.height()), (64, 48));
+        }
+    }
+
+    #[test]
+    fn test_encode_downscales_and_jpeg_is_smaller() {
+        let image = gradient(1024, 512);
+
+        let png = encode(&image, &CaptureOptions::default()).unwrap();
+        let jpeg = encode(&image, &CaptureOptions::vision_default()).unwrap();
+        assert_eq!((png.width, png.height), (1024, 512));
+
+        let small = encode(&image, &CaptureOptions {
+            format: CaptureFormat::Jpeg,
+            quality: Some(60),
+            max_dimension: Some(256),
+        }).unwrap();
+        assert_eq!((small.width, small.height), (256, 128));
+        assert!(small.bytes.len() < jpeg.bytes.len());
+    }
+
+    #[test]
+    fn test_quality_only_applies_to_jpeg() {
+        let image = gradient(128, 96);
+        let with_quality = |format, quality| CaptureOptions { format, quality, max_dimension: None };
+
+        assert_eq!(with_quality(CaptureFormat::Webp, None).applied_quality(), None);
+        assert_eq!(with_quality(CaptureFormat::Png, None).applied_quality(), None);
+
+        let jpeg_low = encode(&image, &with_quality(CaptureFormat::Jpeg, Some(10))).unwrap();
+        let jpeg_high = encode(&image, &with_quality(CaptureFormat::Jpeg, Some(100))).unwrap();
+        assert!(jpeg_low.bytes.len() < jpeg_high.bytes.len());
+        assert_eq!(with_quality(CaptureFormat::Jpeg, Some(10)).applied_quality(), Some(10));
+        assert_eq!(with_quality(CaptureFormat::Jpeg, None).applied_quality(), Some(DEFAULT_JPEG_QUALITY));
+    }
+
+    #[test]
+    fn test_quality_is_refused_for_lossless_formats() {
+        for format in [CaptureFormat::Webp, CaptureFormat::Png] {
+            let settings = CaptureSettings {
+                vision: CaptureOptions { format, quality: Some(50), max_dimension: None },
+                ..CaptureSettings::default()
+            };
+            let error = settings.validate().unwrap_err();
+            assert!(matches!(error, ValidationError::InvalidFormat { ref field, .. } if field == "vision.quality"), "{}", error);
+
+            let lossless = CaptureSettings {
+                vision: CaptureOptions { format, quality: None, max_dimension: None },
+                ..CaptureSettings::default()
+            };
+            assert!(lossless.validate().is_ok());
+        }
+
+        let settings: CaptureSettings = serde_json::from_value(serde_json::json!({
+            "capture": { "format": "webp", "quality": 40, "max_dimension": null },
+            "vision": { "format": "jpeg", "max_dimension": 1568 }
+        }))
+        .unwrap();
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn test_options_validation() {
+        assert!(CaptureSettings::default().validate().is_ok());
+
+        let bad_quality = CaptureOptions { quality: Some(0), ..CaptureOptions::vision_default() };
+        assert!(bad_quality.validate().is_err());
+
+        let bad_dimension = CaptureOptions { max_dimension: Some(16), ..CaptureOptions::default() };
+        assert!(bad_dimension.validate().is_err());
+    }
+}
@@ -0,0 +1,351 @@
+// src-tauri/src/clipboard.rs
+//! 📋 Clipboard integration for Numa
+//!
+//! Features:
+//! - Clipboard text as chat context, clipboard images as vision input
+//! - "Copy answer" and "copy code block N" from a chat response
+//! - Reads gated by an explicit user permission
+//! - Audit trail of every clipboard access
+
+use chrono::{DateTime, Utc};
+use image::RgbaImage;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
+use crate::openai::ChatResponse;
+
+/// Clipboard text sent as context is capped like OCR text
+const MAX_CLIPBOARD_CONTEXT_CHARS: usize = 8000;
+
+/// Number of audit entries kept in memory
+const MAX_AUDIT_ENTRIES: usize = 200;
+
+/// Reads are denied until the user allows them
+static READ_ALLOWED: once_cell::sync::Lazy<Arc<Mutex<bool>>> =
+    once_cell::sync::Lazy::new(|| Arc::new(Mutex::new(false)));
+
+static AUDIT_TRAIL: once_cell::sync::Lazy<Arc<Mutex<VecDeque<ClipboardAuditEntry>>>> =
+    once_cell::sync::Lazy::new(|| Arc::new(Mutex::new(VecDeque::new())));
+
+/// Clipboard operation recorded in the audit trail
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardAction {
+    ReadText,
+    ReadImage,
+    WriteText,
+}
+
+/// One audit entry, never contains clipboard contents
+#[derive(Serialize, Debug, Clone)]
+pub struct ClipboardAuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub action: ClipboardAction,
+    pub allowed: bool,
+    pub bytes: usize,
+    pub error: Option<String>,
+}
+
+fn audit(action: ClipboardAction, allowed: bool, bytes: usize, error: Option<String>) {
+    info!("📋 Clipboard {:?}: allowed={}, {} bytes", action, allowed, bytes);
+
+    let mut trail = AUDIT_TRAIL.lock().unwrap();
+    if trail.len() == MAX_AUDIT_ENTRIES {
+        trail.pop_front();
+    }
+    trail.push_back(ClipboardAuditEntry {
+        timestamp: Utc::now(),
+        action,
+        allowed,
+        bytes,
+        error,
+    });
+}
+
+fn check_read_permission(action: ClipboardAction) -> Result<(), String> {
+    if *READ_ALLOWED.lock().unwrap() {
+        return Ok(());
+    }
+
+    warn!("🚫 Clipboard read denied: permission not granted");
+    audit(action, false, 0, None);
+    Err("Clipboard access not allowed. Enable it in the settings first.".to_string())
+}
+
+/// Platform clipboard, abstracted for tests
+pub trait ClipboardBackend {
+    fn get_text(&mut self) -> Result<String, String>;
+    fn get_image(&mut self) -> Result<RgbaImage, String>;
+    fn set_text(&mut self, text: &str) -> Result<(), String>;
+}
+
+/// System clipboard through `arboard`
+pub struct SystemClipboard(arboard::Clipboard);
+
+impl SystemClipboard {
+    pub fn new() -> Result<Self, String> {
+        arboard::Clipboard::new()
+            .map(Self)
+            .map_err(|e| format!("Clipboard unavailable: {}", e))
+    }
+}
+
+impl ClipboardBackend for SystemClipboard {
+    fn get_text(&mut self) -> Result<String, String> {
+        self.0.get_text().map_err(|e| e.to_string())
+    }
+
+    fn get_image(&mut self) -> Result<RgbaImage, String> {
+        let data = self.0.get_image().map_err(|e| e.to_string())?;
+        RgbaImage::from_raw(data.width as u32, data.height as u32, data.bytes.into_owned())
+            .ok_or_else(|| "Invalid clipboard image".to_string())
+    }
+
+    fn set_text(&mut self, text: &str) -> Result<(), String> {
+        self.0.set_text(text).map_err(|e| e.to_string())
+    }
+}
+
+/// Clipboard contents ready for a chat request
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ClipboardContent {
+    /// For `ChatRequest.context`
+    Text { context: String, truncated: bool },
+    /// For the vision upload path (`get_image_as_base64`, `redact_capture`)
+    Image { image_path: String, width: u32, height: u32 },
+}
+
+/// Read text first, then image, with permission check and audit
+pub fn read_clipboard(backend: &mut dyn ClipboardBackend) -> Result<ClipboardContent, String> {
+    check_read_permission(ClipboardAction::ReadText)?;
+
+    match backend.get_text() {
+        Ok(text) if !text.trim().is_empty() => {
+            audit(ClipboardAction::ReadText, true, text.len(), None);
+            let truncated = text.chars().count() > MAX_CLIPBOARD_CONTEXT_CHARS;
+            let text: String = text.chars().take(MAX_CLIPBOARD_CONTEXT_CHARS).collect();
+            return Ok(ClipboardContent::Text {
+                context: format!("Clipboard contents:\n{}", text),
+                truncated,
+            });
+        }
+        _ => {}
+    }
+
+    match backend.get_image() {
+        Ok(image) => {
+            audit(ClipboardAction::ReadImage, true, image.as_raw().len(), None);
+            let encoded = crate::capture::encode(&image, &crate::capture::current_settings().capture)?;
+            let path = crate::capture::save_to_temp(&encoded, "clipboard")?;
+            Ok(ClipboardContent::Image {
+                image_path: path.to_string_lossy().to_string(),
+                width: encoded.width,
+                height: encoded.height,
+            })
+        }
+        Err(e) => {
+            audit(ClipboardAction::ReadImage, true, 0, Some(e.clone()));
+            Err(format!("Clipboard is empty or unsupported: {}", e))
+        }
+    }
+}
+
+/// Write text to the clipboard, audited
+pub fn write_text(backend: &mut dyn ClipboardBackend, text: &str) -> Result<(), String> {
+    let result = backend.set_text(text);
+    audit(ClipboardAction::WriteText, true, text.len(), result.as_ref().err().cloned());
+    result
+}
+
+/// Fenced code block from a markdown answer
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CodeBlock {
+    pub language: Option<String>,
+    pub code: String,
+}
+
+/// Extract fenced code blocks (``` or ~~~), an unclosed block runs to the end
+pub fn extract_code_blocks(markdown: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut current: Option<(char, usize, Option<String>, Vec<&str>)> = None;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        let fence_char = trimmed.chars().next().filter(|c| *c == '`' || *c == '~');
+        let fence_len = fence_char.map_or(0, |c| trimmed.chars().take_while(|x| *x == c).count());
+
+        match current.take() {
+            None => {
+                if let Some(c) = fence_char.filter(|_| fence_len >= 3) {
+                    let info = trimmed[fence_len..].trim();
+                    let language = info.split_whitespace().next().map(str::to_string);
+                    current = Some((c, fence_len, language, Vec::new()));
+                }
+            }
+            Some((c, len, language, mut lines)) => {
+                let closes = fence_char == Some(c)
+                    && fence_len >= len
+                    && trimmed[fence_len..].trim().is_empty();
+                if closes {
+                    blocks.push(CodeBlock { language, code: lines.join("\n") });
+                } else {
+                    lines.push(line);
+                    current = Some((c, len, language, lines));
+                }
+            }
+        }
+    }
+
+    if let Some((_, _, language, lines)) = current {
+        blocks.push(CodeBlock { language, code: lines.join("\n") });
+    }
+
+    blocks
+}
+
+/// Allow or deny clipboard reads
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all)]
+pub fn set_clipboard_permission(allowed: bool) -> bool {
+    *READ_ALLOWED.lock().unwrap() = allowed;
+    info!("📋 Clipboard read permission set to {}", allowed);
+    allowed
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all)]
+pub fn get_clipboard_permission() -> bool {
+    read_allowed()
+}
+
+pub fn read_allowed() -> bool {
+    *READ_ALLOWED.lock().unwrap()
+}
+
+/// Read the clipboard as chat context or vision input
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+pub fn read_clipboard_context() -> Result<ClipboardContent, String> {
+    read_clipboard(&mut SystemClipboard::new()?)
+}
+
+/// Copy the whole answer
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+pub fn copy_answer(response: ChatResponse) -> Result<(), String> {
+    write_text(&mut SystemClipboard::new()?, &response.message)
+}
+
+/// Copy code block `index` (1-based) of the answer
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+pub fn copy_code_block(response: ChatResponse, index: usize) -> Result<CodeBlock, String> {
+    let blocks = extract_code_blocks(&response.message);
+    let block = index
+        .checked_sub(1)
+        .and_then(|i| blocks.get(i))
+        .cloned()
+        .ok_or_else(|| format!("Code block {} not found ({} in answer)", index, blocks.len()))?;
+
+    write_text(&mut SystemClipboard::new()?, &block.code)?;
+    Ok(block)
+}
+
+/// Recent clipboard accesses, newest first
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all)]
+pub fn get_clipboard_audit() -> Vec<ClipboardAuditEntry> {
+    AUDIT_TRAIL.lock().unwrap().iter().rev().cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Permission and audit trail are global, tests touching them run under this lock
+    static SERIAL: Mutex<()> = Mutex::new(());
+
+    #[derive(Default)]
+    struct FakeClipboard {
+        text: Option<String>,
+        image: Option<RgbaImage>,
+        written: Vec<String>,
+    }
+
+    impl ClipboardBackend for FakeClipboard {
+        fn get_text(&mut self) -> Result<String, String> {
+            self.text.clone().ok_or_else(|| "no text".to_string())
+        }
+
+        fn get_image(&mut self) -> Result<RgbaImage, String> {
+            self.image.clone().ok_or_else(|| "no image".to_string())
+        }
+
+        fn set_text(&mut self, text: &str) -> Result<(), String> {
+            self.written.push(text.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_extract_code_blocks() {
+        let answer = "Try this:\n```rust\nfn main() {}\n```\nor\n~~~\necho hi\n~~~\nand\n````md\n```\nnested\n```\n````\n```py\nprint(1)";
+        let blocks = extract_code_blocks(answer);
+
+        assert_eq!(blocks.len(), 4);
+        assert_eq!(blocks[0], CodeBlock { language: Some("rust".into()), code: "fn main() {}".into() });
+        assert_eq!(blocks[1], CodeBlock { language: None, code: "echo hi".into() });
+        assert_eq!(blocks[2].code, "```\nnested\n```");
+        // Unclosed block (truncated answer)
+        assert_eq!(blocks[3].code, "print(1)");
+
+        assert!(extract_code_blocks("no code here").is_empty());
+    }
+
+    #[test]
+    fn test_reads_require_permission_and_are_audited() {
+        let _guard = SERIAL.lock().unwrap();
+        let mut clipboard = FakeClipboard { text: Some("secret notes".into()), ..Default::default() };
+
+        set_clipboard_permission(false);
+        assert!(read_clipboard(&mut clipboard).is_err());
+        let denied = get_clipboard_audit()[0].clone();
+        assert_eq!(denied.action, ClipboardAction::ReadText);
+        assert!(!denied.allowed);
+
+        set_clipboard_permission(true);
+        let content = read_clipboard(&mut clipboard).unwrap();
+        assert_eq!(content, ClipboardContent::Text {
+            context: "Clipboard contents:\nsecret notes".into(),
+            truncated: false,
+        });
+        let allowed = get_clipboard_audit()[0].clone();
+        assert!(allowed.allowed);
+        assert_eq!(allowed.bytes, 12);
+
+        set_clipboard_permission(false);
+    }
+
+    #[test]
+    fn test_image_fallback_and_write() {
+        let _guard = SERIAL.lock().unwrap();
+        let mut clipboard = FakeClipboard { image: Some(RgbaImage::new(40, 20)), ..Default::default() };
+
+        set_clipboard_permission(true);
+        match read_clipboard(&mut clipboard).unwrap() {
+            ClipboardContent::Image { image_path, width, height } => {
+                assert_eq!((width, height), (40, 20));
+                assert!(std::path::Path::new(&image_path).exists());
+                let _ = std::fs::remove_file(image_path);
+            }
+            other => panic!("expected an image, got {:?}", other),
+        }
+        set_clipboard_permission(false);
+
+        write_text(&mut clipboard, "copied").unwrap();
+        assert_eq!(clipboard.written, vec!["copied".to_string()]);
+        assert_eq!(get_clipboard_audit()[0].action, ClipboardAction::WriteText);
+    }
+}
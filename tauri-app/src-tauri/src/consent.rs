@@ -0,0 +1,192 @@
+// src-tauri/src/consent.rs
+//! ✅ Telemetry consent
+//!
+//! Features:
+//! - Stored in `consent.json` in the app config dir, everything off until the user opts in
+//! - Source of truth for the CSP telemetry endpoints, crash report upload and the OTLP export
+//! - Live windows get the new CSP as soon as consent changes
+//! - Granted from the settings panel only, withdrawn from any window (Do Not Track)
+//! - `consent:changed` sent to every window so they stay in sync
+//! - Same categories as the frontend `PrivacyConsent`
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use tauri::{AppHandle, Emitter};
+use tracing::{info, warn};
+use crate::validation::ValidatedInput;
+
+pub const CONSENT_FILE: &str = "consent.json";
+
+/// Sent to every window with the new `ConsentRecord`
+pub const CHANGED_EVENT: &str = "consent:changed";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, ValidatedInput)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Consent {
+    /// Sentry in the webviews (CSP) and crash report upload
+    pub error_reporting: bool,
+    pub logging: bool,
+    /// Frontend metrics and the OTLP span export
+    pub metrics: bool,
+    pub analytics: bool,
+    pub marketing: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsentRecord {
+    pub consent: Consent,
+    /// `None` until the user has made a choice
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+static CURRENT: Lazy<RwLock<ConsentRecord>> = Lazy::new(|| RwLock::new(ConsentRecord::default()));
+
+/// Set by `init`, consent cannot be changed before
+static FILE: OnceCell<PathBuf> = OnceCell::new();
+
+/// Keeps the file and `CURRENT` in the same order when changes race
+static UPDATE: Mutex<()> = Mutex::new(());
+
+/// Current consent, all off before `init`
+pub fn current() -> Consent {
+    CURRENT.read().unwrap().consent
+}
+
+/// Missing or unreadable file: no consent
+fn load(path: &Path) -> ConsentRecord {
+    match fs::read(path) {
+        Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+            warn!("✅ Ignoring {}: {}", path.display(), e);
+            ConsentRecord::default()
+        }),
+        Err(_) => ConsentRecord::default(),
+    }
+}
+
+fn save(path: &Path, record: &ConsentRecord) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_vec_pretty(record)?)
+}
+
+/// Push consent to the parts that cannot read it on demand
+fn apply(consent: &Consent) {
+    #[cfg(feature = "otel")]
+    crate::otel::set_consent(consent.metrics);
+    #[cfg(not(feature = "otel"))]
+    let _ = consent;
+}
+
+/// Load the stored consent, called once at startup before the consumers start
+pub fn init(config_dir: &Path) {
+    let path = config_dir.join(CONSENT_FILE);
+    let record = load(&path);
+    *CURRENT.write().unwrap() = record;
+    apply(&record.consent);
+    let _ = FILE.set(path);
+
+    info!(
+        "✅ Consent: errorReporting={}, logging={}, metrics={}",
+        record.consent.error_reporting, record.consent.logging, record.consent.metrics
+    );
+}
+
+/// Write `consent` with the time of the choice, the current consent is left untouched
+fn persist(path: &Path, consent: Consent) -> io::Result<ConsentRecord> {
+    let record = ConsentRecord { consent, updated_at: Some(Utc::now()) };
+    save(path, &record)?;
+    Ok(record)
+}
+
+/// Make `record` current, returns the previous consent
+fn swap(record: ConsentRecord) -> Consent {
+    std::mem::replace(&mut *CURRENT.write().unwrap(), record).consent
+}
+
+/// Held by the tests that change the current consent
+#[cfg(test)]
+pub(crate) static TEST_SERIAL: Mutex<()> = Mutex::new(());
+
+/// Make `consent` current, for a test holding `TEST_SERIAL`. Returns the previous consent
+#[cfg(test)]
+pub(crate) fn set_current_for_test(consent: Consent) -> Consent {
+    swap(ConsentRecord { consent, updated_at: None })
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all)]
+pub fn get_consent() -> ConsentRecord {
+    *CURRENT.read().unwrap()
+}
+
+/// Replace the whole consent, then notify every window
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+pub fn set_consent(app: AppHandle, consent: Consent) -> Result<ConsentRecord, String> {
+    change(&app, consent)
+}
+
+/// Withdraw every consent, open to every window since it can only turn things off
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+pub fn revoke_consent(app: AppHandle) -> Result<ConsentRecord, String> {
+    change(&app, Consent::default())
+}
+
+fn change(app: &AppHandle, consent: Consent) -> Result<ConsentRecord, String> {
+    let path = FILE.get().ok_or("Consent storage is not available")?;
+    let (previous, record) = {
+        let _serial = UPDATE.lock().unwrap();
+        // Persisted first: a failed write must not leave consent given for this run only
+        let record = persist(path, consent).map_err(|e| format!("Cannot save consent: {}", e))?;
+        (swap(record), record)
+    };
+    apply(&consent);
+    info!(
+        "✅ Consent updated: errorReporting={}, logging={}, metrics={}",
+        consent.error_reporting, consent.logging, consent.metrics
+    );
+
+    if consent.error_reporting && !previous.error_reporting {
+        crate::crash_upload::resume(app);
+    }
+    crate::csp_manager::refresh(app);
+    if let Err(e) = app.emit(CHANGED_EVENT, record) {
+        warn!("✅ {} not sent: {}", CHANGED_EVENT, e);
+    }
+    Ok(record)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_consent_is_off_until_stored() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config").join(CONSENT_FILE);
+        assert_eq!(load(&path), ConsentRecord::default());
+
+        // Only the file: the process-wide consent read by other tests is not touched
+        let given = Consent { error_reporting: true, metrics: true, ..Consent::default() };
+        let record = persist(&path, given).unwrap();
+        assert!(record.updated_at.is_some());
+        assert_eq!(load(&path), record);
+
+        let withdrawn = persist(&path, Consent::default()).unwrap();
+        assert_eq!(load(&path), withdrawn);
+
+        // Older files without newer categories, or corrupted ones
+        fs::write(&path, r#"{ "consent": { "errorReporting": true }, "updatedAt": null }"#).unwrap();
+        assert_eq!(load(&path).consent, Consent { error_reporting: true, ..Consent::default() });
+        fs::write(&path, "{ not json").unwrap();
+        assert_eq!(load(&path), ConsentRecord::default());
+    }
+}
@@ -0,0 +1,289 @@
+// src-tauri/src/crash.rs
+//! 💥 Persisted crash reports
+//!
+//! Features:
+//! - One JSON report per panic, written by the panic hook (`logging.rs`)
+//! - Message, location, thread, backtrace, process memory, app version, features, last log lines
+//! - Stored in `<app data>/crashes`, only the newest `MAX_REPORTS` are kept
+//! - Listed and exported from the settings panel on the next launch
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::backtrace::Backtrace;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::{info, warn};
+use crate::{logging, redaction};
+
+/// Reports kept, older ones are deleted when a new one is written
+const MAX_REPORTS: usize = 20;
+
+/// Overrides the crash directory
+const CRASH_DIR_ENV: &str = "NUMA_CRASH_DIR";
+
+/// Longest panic message shown in listings
+const SUMMARY_MESSAGE_CHARS: usize = 200;
+
+/// Process memory at the time of the crash
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct MemoryStats {
+    pub resident_bytes: u64,
+    pub virtual_bytes: u64,
+}
+
+impl MemoryStats {
+    pub fn current() -> Option<Self> {
+        memory_stats::memory_stats().map(|stats| Self {
+            resident_bytes: stats.physical_mem as u64,
+            virtual_bytes: stats.virtual_mem as u64,
+        })
+    }
+}
+
+impl fmt::Display for MemoryStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const MB: f64 = 1024.0 * 1024.0;
+        write!(
+            f,
+            "resident {:.1} MB, virtual {:.1} MB",
+            self.resident_bytes as f64 / MB,
+            self.virtual_bytes as f64 / MB
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CrashReport {
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub message: String,
+    pub location: String,
+    pub thread: String,
+    pub backtrace: String,
+    pub memory: Option<MemoryStats>,
+    pub features: Vec<String>,
+    /// Last log lines, already redacted by the logging layers
+    pub recent_logs: Vec<String>,
+}
+
+impl CrashReport {
+    /// Snapshot of the process for a panic happening now
+    pub fn capture(message: &str, location: &str, thread: &str) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: Utc::now(),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            // Panic messages may quote user data
+            message: redaction::redact_log_text(message).into_owned(),
+            location: location.to_string(),
+            thread: thread.to_string(),
+            backtrace: Backtrace::force_capture().to_string(),
+            memory: MemoryStats::current(),
+            features: logging::active_features().into_iter().map(String::from).collect(),
+            recent_logs: logging::recent_lines(),
+        }
+    }
+
+    pub fn summary(&self) -> CrashSummary {
+        CrashSummary {
+            id: self.id.clone(),
+            timestamp: self.timestamp,
+            app_version: self.app_version.clone(),
+            message: self.message.chars().take(SUMMARY_MESSAGE_CHARS).collect(),
+            location: self.location.clone(),
+        }
+    }
+}
+
+/// Listing entry, without backtrace and logs
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CrashSummary {
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    pub app_version: String,
+    pub message: String,
+    pub location: String,
+}
+
+/// Reports directory, set again by `init` once Tauri has resolved the app data dir
+static CRASH_DIR: Lazy<Mutex<Option<PathBuf>>> = Lazy::new(|| Mutex::new(default_dir()));
+
+/// Same location as Tauri's `app_data_dir`, known before the app starts
+fn default_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os(CRASH_DIR_ENV) {
+        return Some(PathBuf::from(dir));
+    }
+    dirs::data_dir().map(|data| data.join(logging::APP_IDENTIFIER).join("crashes"))
+}
+
+pub(crate) fn current_dir() -> Option<PathBuf> {
+    CRASH_DIR.lock().ok().and_then(|dir| dir.clone())
+}
+
+/// Use `dir` for reports and mention crashes of previous runs, called once at startup
+pub fn init(dir: &Path) {
+    if std::env::var_os(CRASH_DIR_ENV).is_none() {
+        *CRASH_DIR.lock().unwrap() = Some(dir.to_path_buf());
+    }
+
+    let Some(dir) = current_dir() else { return };
+    let reports = list_reports(&dir);
+    match reports.first() {
+        Some(latest) => warn!(
+            "💥 {} crash report(s) in {}, latest at {}: {}",
+            reports.len(),
+            dir.display(),
+            latest.timestamp,
+            latest.message
+        ),
+        None => info!("💥 No crash reports in {}", dir.display()),
+    }
+}
+
+/// Called from the panic hook: must not panic, errors are only logged
+pub fn record_panic(message: &str, location: &str, thread: &str) -> Option<PathBuf> {
+    // The hook may run while the panicking thread holds the lock
+    let dir = CRASH_DIR.try_lock().ok().and_then(|dir| dir.clone())?;
+    let report = CrashReport::capture(message, location, thread);
+
+    match write_report(&dir, &report) {
+        Ok(path) => {
+            prune(&dir, MAX_REPORTS);
+            Some(path)
+        }
+        Err(e) => {
+            warn!(target: "panic", "💥 Crash report not written to {}: {}", dir.display(), e);
+            None
+        }
+    }
+}
+
+fn report_path(dir: &Path, id: &str) -> Result<PathBuf, String> {
+    // Ids come from the frontend, only UUIDs map to file names
+    let id = uuid::Uuid::parse_str(id).map_err(|_| format!("Invalid crash report id: {}", id))?;
+    Ok(dir.join(format!("{}.json", id)))
+}
+
+pub fn write_report(dir: &Path, report: &CrashReport) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = report_path(dir, &report.id).map_err(io::Error::other)?;
+    fs::write(&path, serde_json::to_vec_pretty(report)?)?;
+    Ok(path)
+}
+
+/// Newest first
+pub(crate) fn read_reports(dir: &Path) -> Vec<CrashReport> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut reports: Vec<CrashReport> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            let report = fs::read(&path).ok().and_then(|bytes| serde_json::from_slice(&bytes).ok());
+            if report.is_none() {
+                warn!("💥 Unreadable crash report {}", path.display());
+            }
+            report
+        })
+        .collect();
+
+    reports.sort_by_key(|report| std::cmp::Reverse(report.timestamp));
+    reports
+}
+
+pub fn list_reports(dir: &Path) -> Vec<CrashSummary> {
+    read_reports(dir).iter().map(CrashReport::summary).collect()
+}
+
+pub fn read_report(dir: &Path, id: &str) -> Result<CrashReport, String> {
+    let path = report_path(dir, id)?;
+    let bytes = fs::read(&path).map_err(|_| format!("Crash report not found: {}", id))?;
+    serde_json::from_slice(&bytes).map_err(|e| format!("Invalid crash report {}: {}", id, e))
+}
+
+/// Keep the `keep` newest reports
+fn prune(dir: &Path, keep: usize) {
+    for report in read_reports(dir).iter().skip(keep) {
+        if let Ok(path) = report_path(dir, &report.id) {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// List crash reports, newest first
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all)]
+pub fn list_crash_reports() -> Vec<CrashSummary> {
+    current_dir().map(|dir| list_reports(&dir)).unwrap_or_default()
+}
+
+/// Full report as pretty JSON, for saving or attaching to a bug report
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+pub fn export_crash_report(id: String) -> Result<String, String> {
+    let dir = current_dir().ok_or("Crash reports are not available")?;
+    let report = read_report(&dir, &id)?;
+    serde_json::to_string_pretty(&report).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report_at(minutes_ago: i64) -> CrashReport {
+        let mut report = CrashReport::capture("boom", "at src/lib.rs:1", "thread='main'");
+        report.timestamp = Utc::now() - chrono::Duration::minutes(minutes_ago);
+        report
+    }
+
+    #[test]
+    fn test_capture_fills_the_report() {
+        let report = CrashReport::capture(
+            "called `Option::unwrap()` for sk-proj-Ab3dEf6hIj9kLm2NoPq5RsTu8VwXyZ",
+            "at src/lib.rs:42",
+            "thread='main'",
+        );
+
+        assert_eq!(report.app_version, env!("CARGO_PKG_VERSION"));
+        assert!(!report.message.contains("sk-proj-"), "{}", report.message);
+        assert!(!report.backtrace.is_empty());
+        assert!(report.memory.is_some_and(|m| m.resident_bytes > 0));
+        assert!(uuid::Uuid::parse_str(&report.id).is_ok());
+    }
+
+    #[test]
+    fn test_reports_are_listed_newest_first_and_pruned() {
+        let dir = tempfile::tempdir().unwrap();
+        let (old, new) = (report_at(10), report_at(1));
+        write_report(dir.path(), &old).unwrap();
+        write_report(dir.path(), &new).unwrap();
+        fs::write(dir.path().join("garbage.json"), "{").unwrap();
+
+        let listed = list_reports(dir.path());
+        assert_eq!(listed.iter().map(|s| s.id.as_str()).collect::<Vec<_>>(), vec![new.id.as_str(), old.id.as_str()]);
+        assert_eq!(read_report(dir.path(), &old.id).unwrap(), old);
+
+        prune(dir.path(), 1);
+        assert_eq!(list_reports(dir.path()).len(), 1);
+        assert!(read_report(dir.path(), &old.id).is_err());
+    }
+
+    #[test]
+    fn test_ids_cannot_escape_the_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(read_report(dir.path(), "../audit/audit").unwrap_err().contains("Invalid"));
+        assert!(read_report(dir.path(), &uuid::Uuid::new_v4().to_string()).unwrap_err().contains("not found"));
+    }
+}
@@ -5,65 +5,251 @@
 //! - User consent for telemetry/observability
 //! - Stealth mode status
 //! - Security requirements
+//!
+//! Enforced in every Numa webview: a `<meta>` policy computed in Rust and injected at
+//! document start (`with_csp`). When consent or stealth mode changes (`refresh`), open windows
+//! are tightened in place, and reloaded or rebuilt to relax since a page can't loosen its policy
 
-use tauri::{AppHandle, Manager};
-use tracing::{info, debug};
+use once_cell::sync::Lazy;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tauri::webview::PageLoadEvent;
+use tauri::{AppHandle, Manager, Runtime, WebviewWindowBuilder};
+use tracing::{info, debug, warn};
+
+/// Directives a `<meta>` policy cannot carry, browsers ignore them with a warning
+const HEADER_ONLY_DIRECTIVES: &[&str] = &["report-uri", "frame-ancestors", "sandbox"];
+
+/// Policy of one window built with `with_csp`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct WindowPolicy {
+    /// Policy baked in the initialization script, run again on every page load
+    built_with_telemetry: bool,
+    /// Policy enforced now, stricter than the built one after a `refresh`
+    telemetry: bool,
+}
+
+/// Windows built with `with_csp`, by label
+#[derive(Debug, Default)]
+struct WindowPolicies(BTreeMap<String, WindowPolicy>);
+
+impl WindowPolicies {
+    /// Initialization script of a window built now
+    fn build(&mut self, label: &str, telemetry: bool) -> String {
+        self.0.insert(label.to_string(), WindowPolicy { built_with_telemetry: telemetry, telemetry });
+        initialization_script(telemetry)
+    }
+
+    /// What the open windows need for the new state. Rebuilt windows are updated by `build`
+    fn refresh(&mut self, telemetry: bool) -> Changes {
+        let mut changes = Changes::default();
+
+        for (label, policy) in self.0.iter_mut().filter(|(_, policy)| policy.telemetry != telemetry) {
+            if !telemetry {
+                policy.telemetry = false;
+                changes.tighten.push(label.clone());
+            } else if policy.built_with_telemetry {
+                policy.telemetry = true;
+                changes.reload.push(label.clone());
+            } else {
+                changes.rebuild.push(label.clone());
+            }
+        }
+
+        changes
+    }
+
+    /// A page load runs the built policy again, tightened windows need `tighten_script` on top
+    fn needs_tightening_on_load(&self, label: &str) -> bool {
+        self.0.get(label).is_some_and(|policy| policy.built_with_telemetry && !policy.telemetry)
+    }
+
+    /// Policy enforced in `label`, `None` when it was not built with `with_csp`
+    #[cfg(test)]
+    fn enforced(&self, label: &str) -> Option<String> {
+        self.0.get(label).map(|policy| meta_policy(&build_policy(policy.telemetry)))
+    }
+}
+
+/// Windows to update after a consent or stealth change, by label
+#[derive(Debug, Default, PartialEq)]
+struct Changes {
+    /// Stricter `<meta>` added to the live page
+    tighten: Vec<String>,
+    /// Built with the relaxed policy: a reload runs it again
+    reload: Vec<String>,
+    /// Built with the strict policy: relaxing needs a new initialization script
+    rebuild: Vec<String>,
+}
+
+static WINDOWS: Lazy<Mutex<WindowPolicies>> = Lazy::new(|| Mutex::new(WindowPolicies::default()));
+
+/// Telemetry endpoints are allowed only with consent AND outside stealth mode
+pub fn telemetry_allowed(consent: bool, stealth_active: bool) -> bool {
+    consent && !stealth_active
+}
+
+fn current_telemetry<R: Runtime>(app: &AppHandle<R>) -> bool {
+    telemetry_allowed(check_telemetry_consent(), app.state::<crate::stealth::StealthState>().is_active())
+}
+
+/// Full policy, with the telemetry endpoints when `telemetry` is allowed
+pub fn build_policy(telemetry: bool) -> String {
+    let mut csp = build_base_csp();
+    if telemetry {
+        add_telemetry_csp(&mut csp);
+    }
+    // Add development endpoints in debug builds
+    add_development_csp(&mut csp);
+    csp
+}
 
 /// Generate CSP policy based on current application state
 #[tauri::command]
-pub fn get_dynamic_csp_policy(app: AppHandle) -> String {
+#[tracing::instrument(target = "ipc", skip_all)]
+pub fn get_dynamic_csp_policy<R: Runtime>(app: AppHandle<R>) -> String {
     let stealth_active = app.state::<crate::stealth::StealthState>().is_active();
     let telemetry_consent = check_telemetry_consent();
     
     debug!("🛡️ Generating CSP policy - stealth: {}, telemetry: {}", 
            stealth_active, telemetry_consent);
     
-    let mut csp = build_base_csp();
-    
     // Add telemetry endpoints only if consented AND not in stealth mode
-    if telemetry_consent && !stealth_active {
-        add_telemetry_csp(&mut csp);
+    let telemetry = telemetry_allowed(telemetry_consent, stealth_active);
+    if telemetry {
         info!("🔗 CSP: Telemetry endpoints enabled");
     } else {
         info!("🔒 CSP: Telemetry endpoints blocked (stealth={}, consent={})", 
               stealth_active, telemetry_consent);
     }
     
-    // Add development endpoints in debug builds
-    add_development_csp(&mut csp);
-    
+    let csp = build_policy(telemetry);
     debug!("🛡️ Final CSP: {}", csp);
     csp
 }
 
-/// Check if user has consented to telemetry
-/// TODO: Implement proper consent checking (localStorage, config file, etc.)
+/// Policy without the directives `<meta>` cannot deliver
+pub fn meta_policy(policy: &str) -> String {
+    policy
+        .split(';')
+        .map(str::trim)
+        .filter(|directive| {
+            let name = directive.split_whitespace().next().unwrap_or_default();
+            !directive.is_empty() && !HEADER_ONLY_DIRECTIVES.contains(&name)
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Script adding `policy` as a `<meta>`, which is only honored inside `<head>`
+fn meta_script(policy: &str) -> String {
+    let content = serde_json::to_string(&meta_policy(policy)).unwrap_or_else(|_| "\"\"".to_string());
+    format!(
+        r#"(function () {{
+  var meta = document.createElement("meta");
+  meta.httpEquiv = "Content-Security-Policy";
+  meta.content = {content};
+  if (document.head) {{ document.head.prepend(meta); return; }}
+  new MutationObserver(function (_, observer) {{
+    if (document.head) {{ observer.disconnect(); document.head.prepend(meta); }}
+  }}).observe(document, {{ childList: true, subtree: true }});
+}})();"#
+    )
+}
+
+/// Injected at document start: the one policy computed in Rust when the window was built,
+/// nothing the page stores can select another one
+pub fn initialization_script(telemetry: bool) -> String {
+    meta_script(&build_policy(telemetry))
+}
+
+/// Run in live windows losing telemetry, the stricter policy adds up with the built one
+pub fn tighten_script() -> String {
+    meta_script(&build_policy(false))
+}
+
+/// Enforce the current policy in a window being built
+pub fn with_csp<'a, R: Runtime, M: Manager<R>>(
+    app: &AppHandle<R>,
+    label: &str,
+    builder: WebviewWindowBuilder<'a, R, M>,
+) -> WebviewWindowBuilder<'a, R, M> {
+    let telemetry = current_telemetry(app);
+    let script = WINDOWS.lock().unwrap().build(label, telemetry);
+    debug!("🛡️ CSP injected in '{}' (telemetry: {})", label, telemetry);
+
+    builder.initialization_script(&script).on_page_load(|window, payload| {
+        // A reload runs the built policy again: keep the window as tight as `refresh` left it
+        if payload.event() != PageLoadEvent::Started
+            || !WINDOWS.lock().unwrap().needs_tightening_on_load(window.label())
+        {
+            return;
+        }
+        if let Err(e) = window.eval(&tighten_script()) {
+            warn!("🛡️ CSP not re-applied to '{}': {}", window.label(), e);
+        }
+    })
+}
+
+/// Policy recorded by `with_csp` for `label`
+#[cfg(test)]
+pub fn enforced_policy(label: &str) -> Option<String> {
+    WINDOWS.lock().unwrap().enforced(label)
+}
+
+/// Re-apply the policy to live windows after a consent or stealth change
+pub fn refresh(app: &AppHandle) {
+    let changes = WINDOWS.lock().unwrap().refresh(current_telemetry(app));
+
+    let script = tighten_script();
+    for label in &changes.tighten {
+        let Some(window) = app.get_webview_window(label) else {
+            continue;
+        };
+        if let Err(e) = window.eval(&script) {
+            warn!("🛡️ CSP not re-applied to '{}': {}", label, e);
+        }
+    }
+    for label in &changes.reload {
+        let Some(window) = app.get_webview_window(label) else {
+            continue;
+        };
+        if let Err(e) = window.reload() {
+            warn!("🛡️ '{}' not reloaded with its relaxed CSP: {}", label, e);
+        }
+    }
+    crate::rebuild_windows(app, &changes.rebuild);
+
+    if changes != Changes::default() {
+        info!("🛡️ CSP re-applied to open windows: {:?}", changes);
+    }
+}
+
+/// Check if user has consented to telemetry (error reporting, stored by `consent.rs`)
 fn check_telemetry_consent() -> bool {
-    // For now, return false to be privacy-first
-    // This should check actual user consent from storage
-    false
+    crate::consent::current().error_reporting
 }
 
 /// Build base CSP policy that's always applied
 fn build_base_csp() -> String {
-    let mut policy = Vec::new();
-    
-    // Core policies
-    policy.push("default-src 'self'");
-    policy.push("script-src 'self' 'unsafe-inline'"); // Unsafe-inline needed for React
-    policy.push("style-src 'self' 'unsafe-inline'");  // Unsafe-inline needed for styling
-    policy.push("img-src 'self' data: blob:");         // Data URLs for images
-    policy.push("font-src 'self' data:");              // Data URLs for fonts
-    
-    // Object and embed policies
-    policy.push("object-src 'none'");
-    policy.push("embed-src 'none'");
-    
-    // Form actions
-    policy.push("form-action 'self'");
-    
-    // Base connect-src (will be extended conditionally)
-    policy.push("connect-src 'self'");
+    let policy = [
+        // Core policies
+        "default-src 'self'",
+        "script-src 'self' 'unsafe-inline'", // Unsafe-inline needed for React
+        "style-src 'self' 'unsafe-inline'",  // Unsafe-inline needed for styling
+        "img-src 'self' data: blob:",         // Data URLs for images
+        "font-src 'self' data:",              // Data URLs for fonts
+        
+        // Object and embed policies
+        "object-src 'none'",
+        "embed-src 'none'",
+        
+        // Form actions
+        "form-action 'self'",
+        
+        // Base connect-src (will be extended conditionally)
+        "connect-src 'self'",
+    ];
     
     policy.join("; ")
 }
@@ -101,15 +287,13 @@ fn add_development_csp(csp: &mut String) {
 }
 
 /// Update CSP based on stealth mode changes
-pub fn on_stealth_changed(_app: &AppHandle, stealth_active: bool) {
+pub fn on_stealth_changed(app: &AppHandle, stealth_active: bool) {
     if stealth_active {
         info!("🕵️ Stealth mode activated - CSP will block telemetry");
-        // In stealth mode, ensure no external connections
-        // This would require updating the webview CSP dynamically
-        // For now, we log the intent
     } else {
         info!("🌐 Stealth mode deactivated - CSP may allow telemetry if consented");
     }
+    refresh(app);
 }
 
 /// Validate CSP policy for common security issues
@@ -144,6 +328,7 @@ fn validate_csp_policy(policy: &str) -> Vec<String> {
 
 /// Get CSP policy for specific use case
 #[tauri::command] 
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
 pub fn get_csp_for_context(context: String) -> Result<String, String> {
     match context.as_str() {
         "main" => {
@@ -178,8 +363,9 @@ mod tests {
         let unsafe_policy = "script-src 'self' 'unsafe-eval'; object-src *";
         let warnings = validate_csp_policy(unsafe_policy);
         
-        assert!(warnings.len() > 0);
-        assert!(warnings.iter().any(|w| w.contains("unsafe-eval")));
+        assert!(!warnings.is_empty());
+        // 'unsafe-eval' is expected in debug builds
+        assert_eq!(warnings.iter().any(|w| w.contains("unsafe-eval")), !cfg!(debug_assertions));
         assert!(warnings.iter().any(|w| w.contains("wildcard")));
     }
     
@@ -191,4 +377,72 @@ mod tests {
         assert!(base_csp.contains("sentry.io"));
         assert!(base_csp.contains("report-uri"));
     }
+    
+    #[test]
+    fn test_window_policy_follows_consent_and_stealth() {
+        let mut windows = WindowPolicies::default();
+        let strict = meta_policy(&build_policy(false));
+        let telemetry = meta_policy(&build_policy(true));
+        let labels = |labels: &[&str]| labels.iter().map(|label| label.to_string()).collect::<Vec<_>>();
+
+        // Default: no consent
+        let script = windows.build("hud", telemetry_allowed(false, false));
+        assert_eq!(windows.enforced("hud").as_deref(), Some(strict.as_str()));
+        assert!(!script.contains("sentry.io"));
+
+        // Consent given: a window built strict is rebuilt, which records its new policy
+        let changes = windows.refresh(telemetry_allowed(true, false));
+        assert_eq!(changes, Changes { rebuild: labels(&["hud"]), ..Changes::default() });
+        windows.build("hud", telemetry_allowed(true, false));
+        windows.build("panel", telemetry_allowed(true, false));
+        assert_eq!(windows.enforced("hud").as_deref(), Some(telemetry.as_str()));
+        assert_eq!(windows.enforced("panel").as_deref(), Some(telemetry.as_str()));
+
+        // Stealth mode: tightened in place, and again on every reload
+        let changes = windows.refresh(telemetry_allowed(true, true));
+        assert_eq!(changes, Changes { tighten: labels(&["hud", "panel"]), ..Changes::default() });
+        assert_eq!(windows.enforced("panel").as_deref(), Some(strict.as_str()));
+        assert!(windows.needs_tightening_on_load("panel"));
+        assert!(!windows.build("input", telemetry_allowed(true, true)).contains("sentry.io"));
+
+        // Stealth mode off: windows built relaxed reload, the one built in stealth mode is rebuilt
+        let changes = windows.refresh(telemetry_allowed(true, false));
+        assert_eq!(changes, Changes { reload: labels(&["hud", "panel"]), rebuild: labels(&["input"]), ..Changes::default() });
+        assert_eq!(windows.enforced("panel").as_deref(), Some(telemetry.as_str()));
+        assert!(!windows.needs_tightening_on_load("panel"));
+        windows.build("input", telemetry_allowed(true, false));
+
+        // Consent revoked: strict everywhere, reloads included
+        let changes = windows.refresh(telemetry_allowed(false, false));
+        assert_eq!(changes, Changes { tighten: labels(&["hud", "input", "panel"]), ..Changes::default() });
+        for label in ["hud", "input", "panel"] {
+            assert_eq!(windows.enforced(label).as_deref(), Some(strict.as_str()));
+            assert!(windows.needs_tightening_on_load(label));
+        }
+        assert_eq!(windows.refresh(telemetry_allowed(false, false)), Changes::default());
+        assert_eq!(windows.enforced("unknown"), None);
+    }
+
+    #[test]
+    fn test_page_cannot_select_the_policy() {
+        // Each script carries exactly one policy and reads nothing the page could set
+        for script in [initialization_script(false), initialization_script(true), tighten_script()] {
+            assert_eq!(script.matches("meta.content =").count(), 1);
+            for page_state in ["Storage", "cookie", "location", "window.name"] {
+                assert!(!script.contains(page_state), "{} in {}", page_state, script);
+            }
+            assert!(!script.contains("report-uri"));
+        }
+        assert!(!initialization_script(false).contains("sentry.io"));
+        assert!(!tighten_script().contains("sentry.io"));
+        assert!(initialization_script(true).contains("sentry.io"));
+    }
+
+    #[test]
+    fn test_meta_policy_drops_header_only_directives() {
+        let policy = meta_policy(&build_policy(true));
+        assert!(policy.contains("https://api.sentry.io"));
+        assert!(!policy.contains("report-uri"));
+        assert!(!policy.ends_with(';'));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,318 @@
+// src-tauri/src/diagnostics.rs
+//! 🩺 Diagnostics bundle
+//!
+//! Features:
+//! - `export_diagnostics` writes one zip to a file picked by the user
+//! - Log files (redacted again on export), crash reports, system info and active features
+//! - Window rects, sanitized settings and rate limiter quotas
+//! - No secrets: API keys stay in the keychain, custom redaction patterns are kept by name only
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::DialogExt;
+use tracing::{info, warn};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+use crate::capture::CaptureSettings;
+use crate::crash::{self, MemoryStats};
+use crate::crash_upload::{self, CrashReportingStatus};
+use crate::logging::{self, LogLevels, SystemInfo};
+use crate::rate_limit::{self, Quota};
+use crate::redaction::{self, RedactionSettings};
+use crate::stealth::StealthState;
+
+/// Suggested file name, followed by the export time
+const BUNDLE_NAME: &str = "numa-diagnostics";
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowRect {
+    pub label: String,
+    /// Physical pixels, from `get_rect`
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    pub visible: bool,
+    pub scale_factor: f64,
+}
+
+/// Redaction settings without the custom patterns, which spell out what they mask
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionSummary {
+    pub providers: HashMap<String, bool>,
+    pub custom_patterns: Vec<String>,
+    pub blur_images: bool,
+}
+
+impl From<RedactionSettings> for RedactionSummary {
+    fn from(settings: RedactionSettings) -> Self {
+        Self {
+            providers: settings.providers,
+            custom_patterns: settings.custom_patterns.into_iter().map(|pattern| pattern.name).collect(),
+            blur_images: settings.blur_images,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SanitizedSettings {
+    pub capture: CaptureSettings,
+    pub redaction: RedactionSummary,
+    pub clipboard_read_allowed: bool,
+    pub crash_reporting: CrashReportingStatus,
+    pub log_levels: Option<LogLevels>,
+    pub stealth_active: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct SystemReport {
+    generated_at: DateTime<Utc>,
+    system: SystemInfo,
+    memory: Option<MemoryStats>,
+}
+
+/// Everything read from the app, gathered before writing the zip off the async runtime
+struct Diagnostics {
+    system: SystemReport,
+    windows: Vec<WindowRect>,
+    settings: SanitizedSettings,
+    rate_limits: Vec<Quota>,
+    log_files: Vec<PathBuf>,
+    crash_dir: Option<PathBuf>,
+}
+
+impl Diagnostics {
+    fn collect(app: &AppHandle) -> Self {
+        Self {
+            system: SystemReport {
+                generated_at: Utc::now(),
+                system: logging::system_info(),
+                memory: MemoryStats::current(),
+            },
+            windows: window_rects(app),
+            settings: SanitizedSettings {
+                capture: crate::capture::current_settings(),
+                redaction: redaction::current_settings().into(),
+                clipboard_read_allowed: crate::clipboard::read_allowed(),
+                crash_reporting: crash_upload::status(app),
+                log_levels: logging::current_levels(),
+                stealth_active: app.state::<StealthState>().is_active(),
+            },
+            rate_limits: rate_limit::quotas(),
+            log_files: logging::log_files(),
+            crash_dir: crash::current_dir(),
+        }
+    }
+}
+
+fn window_rects(app: &AppHandle) -> Vec<WindowRect> {
+    let mut windows: Vec<WindowRect> = app
+        .webview_windows()
+        .into_iter()
+        .filter_map(|(label, window)| {
+            let rect = crate::get_rect(&window)
+                .inspect_err(|e| warn!("🩺 No rect for window '{}': {}", label, e))
+                .ok()?;
+            Some(WindowRect {
+                x: rect.x,
+                y: rect.y,
+                width: rect.w,
+                height: rect.h,
+                visible: window.is_visible().unwrap_or(false),
+                scale_factor: window.scale_factor().unwrap_or(1.0),
+                label,
+            })
+        })
+        .collect();
+    windows.sort_by(|a, b| a.label.cmp(&b.label));
+    windows
+}
+
+fn add_file<W: Write + std::io::Seek>(zip: &mut ZipWriter<W>, name: &str, bytes: &[u8]) -> Result<(), String> {
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    zip.start_file(name, options).map_err(|e| e.to_string())?;
+    zip.write_all(bytes).map_err(|e| e.to_string())
+}
+
+fn add_json<W: Write + std::io::Seek, T: Serialize>(zip: &mut ZipWriter<W>, name: &str, value: &T) -> Result<(), String> {
+    let json = serde_json::to_vec_pretty(value).map_err(|e| e.to_string())?;
+    add_file(zip, name, &json)
+}
+
+/// Write the bundle to `path`, returns the number of files in it
+fn write_bundle(path: &Path, diagnostics: &Diagnostics) -> Result<usize, String> {
+    let file = File::create(path).map_err(|e| format!("Cannot create {}: {}", path.display(), e))?;
+    let mut zip = ZipWriter::new(file);
+    let mut files = 0;
+
+    add_json(&mut zip, "system.json", &diagnostics.system)?;
+    add_json(&mut zip, "windows.json", &diagnostics.windows)?;
+    add_json(&mut zip, "settings.json", &diagnostics.settings)?;
+    add_json(&mut zip, "rate_limits.json", &diagnostics.rate_limits)?;
+    files += 4;
+
+    // Files written before redaction existed, or by an older build, are masked here
+    for log in &diagnostics.log_files {
+        let (Some(name), Ok(bytes)) = (log.file_name().and_then(|name| name.to_str()), fs::read(log)) else {
+            warn!("🩺 Skipping unreadable log file {}", log.display());
+            continue;
+        };
+        let content = String::from_utf8_lossy(&bytes);
+        add_file(&mut zip, &format!("logs/{}", name), redaction::redact_log_text(&content).as_bytes())?;
+        files += 1;
+    }
+    // File logs disabled: the in-memory lines are all there is
+    if diagnostics.log_files.is_empty() {
+        add_file(&mut zip, "logs/recent.log", logging::recent_lines().join("\n").as_bytes())?;
+        files += 1;
+    }
+
+    for report in diagnostics.crash_dir.as_deref().map(crash::read_reports).unwrap_or_default() {
+        add_json(&mut zip, &format!("crashes/{}.json", report.id), &report)?;
+        files += 1;
+    }
+
+    zip.finish().map_err(|e| e.to_string())?;
+    Ok(files)
+}
+
+/// Ask where to save the bundle, `None` when the user cancels
+async fn pick_destination(app: &AppHandle) -> Result<Option<PathBuf>, String> {
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    app.dialog()
+        .file()
+        .set_title("Export diagnostics")
+        .set_file_name(format!("{}-{}.zip", BUNDLE_NAME, Utc::now().format("%Y%m%d-%H%M%S")))
+        .add_filter("Zip", &["zip"])
+        .save_file(move |path| {
+            let _ = sender.send(path);
+        });
+
+    match receiver.await.map_err(|e| e.to_string())? {
+        Some(path) => path.into_path().map(Some).map_err(|e| e.to_string()),
+        None => Ok(None),
+    }
+}
+
+/// Zip logs, crash reports, system info, windows and settings to a file picked by the user,
+/// returns its path, or `None` when cancelled
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+pub async fn export_diagnostics(app: AppHandle) -> Result<Option<PathBuf>, String> {
+    let Some(path) = pick_destination(&app).await? else {
+        info!("🩺 Diagnostics export cancelled");
+        return Ok(None);
+    };
+
+    let diagnostics = Diagnostics::collect(&app);
+    let destination = path.clone();
+    let files = tauri::async_runtime::spawn_blocking(move || write_bundle(&destination, &diagnostics))
+        .await
+        .map_err(|e| e.to_string())??;
+
+    info!("🩺 Diagnostics bundle written to {} ({} files)", path.display(), files);
+    Ok(Some(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redaction::CustomPattern;
+    use std::io::Read;
+
+    fn diagnostics(log_files: Vec<PathBuf>, crash_dir: Option<PathBuf>) -> Diagnostics {
+        let mut redaction = RedactionSettings::default();
+        redaction.custom_patterns.push(CustomPattern { name: "project".into(), pattern: "Project Falcon".into() });
+
+        Diagnostics {
+            system: SystemReport { generated_at: Utc::now(), system: logging::system_info(), memory: None },
+            windows: vec![WindowRect {
+                label: "hud".into(),
+                x: 100,
+                y: 40,
+                width: 600,
+                height: 48,
+                visible: true,
+                scale_factor: 2.0,
+            }],
+            settings: SanitizedSettings {
+                capture: CaptureSettings::default(),
+                redaction: redaction.into(),
+                clipboard_read_allowed: false,
+                crash_reporting: CrashReportingStatus {
+                    consent: false,
+                    privacy_mode: false,
+                    endpoint_host: None,
+                    pending: 0,
+                },
+                log_levels: None,
+                stealth_active: false,
+            },
+            rate_limits: vec![],
+            log_files,
+            crash_dir,
+        }
+    }
+
+    fn read_bundle(path: &Path) -> HashMap<String, String> {
+        let mut archive = zip::ZipArchive::new(File::open(path).unwrap()).unwrap();
+        (0..archive.len())
+            .map(|i| {
+                let mut file = archive.by_index(i).unwrap();
+                let mut content = String::new();
+                file.read_to_string(&mut content).unwrap();
+                (file.name().to_string(), content)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_bundle_contents_are_redacted() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("numa.2026-10-18.log");
+        fs::write(&log, "INFO key sk-proj-Ab3dEf6hIj9kLm2NoPq5RsTu8VwXyZ for jane.doe@example.com\n").unwrap();
+
+        let crashes = dir.path().join("crashes");
+        let report = crash::CrashReport::capture("boom", "src/lib.rs:1:1", "main");
+        crash::write_report(&crashes, &report).unwrap();
+
+        let path = dir.path().join("bundle.zip");
+        let files = write_bundle(&path, &diagnostics(vec![log], Some(crashes))).unwrap();
+        let bundle = read_bundle(&path);
+
+        assert_eq!(files, bundle.len());
+        for name in ["system.json", "windows.json", "settings.json", "rate_limits.json", "logs/numa.2026-10-18.log"] {
+            assert!(bundle.contains_key(name), "{} missing", name);
+        }
+        assert!(bundle.contains_key(&format!("crashes/{}.json", report.id)));
+
+        let log = &bundle["logs/numa.2026-10-18.log"];
+        assert!(log.contains("[REDACTED:API_KEY]") && log.contains("[REDACTED:EMAIL]"), "{}", log);
+        assert!(!log.contains("sk-proj") && !log.contains("jane.doe"));
+
+        assert!(bundle["settings.json"].contains("\"project\""));
+        assert!(!bundle["settings.json"].contains("Project Falcon"), "custom patterns are kept by name only");
+        assert!(bundle["windows.json"].contains("\"scaleFactor\": 2.0"));
+        assert!(bundle["system.json"].contains(env!("CARGO_PKG_VERSION")));
+    }
+
+    #[test]
+    fn test_recent_lines_without_log_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bundle.zip");
+        write_bundle(&path, &diagnostics(vec![], None)).unwrap();
+
+        let bundle = read_bundle(&path);
+        assert!(bundle.contains_key("logs/recent.log"));
+        assert!(!bundle.keys().any(|name| name.starts_with("crashes/")));
+    }
+}
@@ -0,0 +1,430 @@
+// src-tauri/src/history.rs
+//! 🗂️ Capture history for Numa
+//!
+//! Features:
+//! - Persistent index of captures (JSON in the app data directory)
+//! - Metadata: timestamp, display, dimensions, OCR text, conversation
+//! - Search by OCR text
+//! - Cached thumbnails
+//! - Deletion of captures and derived files
+
+use chrono::{DateTime, Utc};
+use image::imageops::FilterType;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn, debug};
+use crate::capture::CaptureFormat;
+
+const INDEX_FILE: &str = "index.json";
+const THUMBNAILS_DIR: &str = "thumbnails";
+
+/// Thumbnail bounds (long edge, pixels)
+const DEFAULT_THUMBNAIL_SIZE: u32 = 256;
+pub(crate) const MIN_THUMBNAIL_SIZE: u32 = 32;
+pub(crate) const MAX_THUMBNAIL_SIZE: u32 = 1024;
+
+pub(crate) const MAX_QUERY_LEN: usize = 256;
+const DEFAULT_LIST_LIMIT: usize = 50;
+
+/// One capture in the history
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CaptureRecord {
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub display_id: u32,
+    pub width: u32,
+    pub height: u32,
+    pub format: CaptureFormat,
+    pub path: PathBuf,
+    /// Set when a single window was captured instead of a display
+    #[serde(default)]
+    pub window: Option<CapturedWindow>,
+    pub ocr_text: Option<String>,
+    pub conversation_id: Option<String>,
+}
+
+/// Window a capture was taken from
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CapturedWindow {
+    pub title: String,
+    pub app_name: String,
+}
+
+/// Capture metadata before it enters the history
+#[derive(Debug, Clone)]
+pub struct NewCapture {
+    pub path: PathBuf,
+    pub display_id: u32,
+    pub width: u32,
+    pub height: u32,
+    pub format: CaptureFormat,
+    pub window: Option<CapturedWindow>,
+}
+
+/// Rename an unreadable index to `index.json.corrupt-<timestamp>` next to it
+fn set_aside(index_path: &Path) -> Result<PathBuf, String> {
+    let kept = index_path.with_file_name(format!("{}.corrupt-{}", INDEX_FILE, Utc::now().format("%Y%m%dT%H%M%S%.3fZ")));
+    fs::rename(index_path, &kept).map_err(|e| format!("Cannot set aside the unreadable capture index: {}", e))?;
+    Ok(kept)
+}
+
+/// On-disk index of captures, newest last
+#[derive(Debug)]
+pub struct CaptureIndex {
+    dir: PathBuf,
+    records: Vec<CaptureRecord>,
+}
+
+impl CaptureIndex {
+    /// Open (or create) the index stored in `dir`
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self, String> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+        let index_path = dir.join(INDEX_FILE);
+        let records = match fs::read(&index_path) {
+            Ok(content) => match serde_json::from_slice(&content) {
+                Ok(records) => records,
+                Err(e) => {
+                    // A corrupted index must not block new captures, nor be overwritten by the next save
+                    let kept = set_aside(&index_path)?;
+                    warn!("🗂️ Capture index unreadable, starting fresh (kept as {}): {}", kept.display(), e);
+                    Vec::new()
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(format!("Cannot read capture index: {}", e)),
+        };
+
+        Ok(Self { dir, records })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Write the index atomically (temp file + rename)
+    fn save(&self) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(&self.records).map_err(|e| e.to_string())?;
+        let tmp = self.dir.join(format!("{}.tmp", INDEX_FILE));
+        fs::write(&tmp, content).map_err(|e| e.to_string())?;
+        fs::rename(&tmp, self.dir.join(INDEX_FILE)).map_err(|e| e.to_string())
+    }
+
+    /// Move a capture file into the history and record it
+    pub fn import(&mut self, capture: NewCapture) -> Result<CaptureRecord, String> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let path = self.dir.join(format!("{}.{}", id, capture.format.extension()));
+
+        // rename fails across filesystems (temp dir on another volume)
+        if fs::rename(&capture.path, &path).is_err() {
+            fs::copy(&capture.path, &path).map_err(|e| e.to_string())?;
+            let _ = fs::remove_file(&capture.path);
+        }
+
+        let record = CaptureRecord {
+            id,
+            created_at: Utc::now(),
+            display_id: capture.display_id,
+            width: capture.width,
+            height: capture.height,
+            format: capture.format,
+            path,
+            window: capture.window,
+            ocr_text: None,
+            conversation_id: None,
+        };
+
+        self.records.push(record.clone());
+        self.save()?;
+        debug!("🗂️ Capture {} added to history", record.id);
+        Ok(record)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&CaptureRecord> {
+        self.records.iter().find(|r| r.id == id)
+    }
+
+    fn get_mut(&mut self, id: &str) -> Result<&mut CaptureRecord, String> {
+        self.records
+            .iter_mut()
+            .find(|r| r.id == id)
+            .ok_or_else(|| format!("Capture introuvable: {}", id))
+    }
+
+    /// Newest captures first
+    pub fn list(&self, limit: usize) -> Vec<CaptureRecord> {
+        self.records.iter().rev().take(limit).cloned().collect()
+    }
+
+    /// Case-insensitive search in OCR text, newest first
+    pub fn search(&self, query: &str, limit: usize) -> Vec<CaptureRecord> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return self.list(limit);
+        }
+
+        self.records
+            .iter()
+            .rev()
+            .filter(|r| {
+                r.ocr_text
+                    .as_deref()
+                    .is_some_and(|text| text.to_lowercase().contains(&query))
+            })
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    pub fn set_ocr_text(&mut self, id: &str, text: Option<String>) -> Result<(), String> {
+        self.get_mut(id)?.ocr_text = text.filter(|t| !t.trim().is_empty());
+        self.save()
+    }
+
+    pub fn link_conversation(&mut self, id: &str, conversation_id: &str) -> Result<(), String> {
+        self.get_mut(id)?.conversation_id = Some(conversation_id.to_string());
+        self.save()
+    }
+
+    /// Thumbnail as PNG bytes, generated once per size
+    pub fn thumbnail(&self, id: &str, size: u32) -> Result<Vec<u8>, String> {
+        let record = self.get(id).ok_or_else(|| format!("Capture introuvable: {}", id))?;
+        let thumb_path = self.dir.join(THUMBNAILS_DIR).join(format!("{}_{}.png", id, size));
+
+        if let Ok(bytes) = fs::read(&thumb_path) {
+            return Ok(bytes);
+        }
+
+        let image = image::open(&record.path).map_err(|e| format!("Erreur de lecture: {}", e))?;
+        let thumbnail = image.resize(size, size, FilterType::Triangle);
+
+        let mut bytes = Vec::new();
+        thumbnail
+            .write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageOutputFormat::Png)
+            .map_err(|e| e.to_string())?;
+
+        if let Some(parent) = thumb_path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Err(e) = fs::write(&thumb_path, &bytes) {
+            warn!("🗂️ Thumbnail cache not written: {}", e);
+        }
+
+        Ok(bytes)
+    }
+
+    /// Remove a capture, its thumbnails and redacted copies
+    pub fn delete(&mut self, id: &str) -> Result<CaptureRecord, String> {
+        let position = self
+            .records
+            .iter()
+            .position(|r| r.id == id)
+            .ok_or_else(|| format!("Capture introuvable: {}", id))?;
+        let record = self.records.remove(position);
+
+        let _ = fs::remove_file(&record.path);
+        let _ = fs::remove_file(self.dir.join(format!("{}_redacted.png", id)));
+        if let Ok(entries) = fs::read_dir(self.dir.join(THUMBNAILS_DIR)) {
+            for entry in entries.flatten() {
+                if entry.file_name().to_string_lossy().starts_with(&format!("{}_", id)) {
+                    let _ = fs::remove_file(entry.path());
+                }
+            }
+        }
+
+        self.save()?;
+        info!("🗑️ Capture {} deleted", id);
+        Ok(record)
+    }
+}
+
+/// Capture ids are UUIDs, anything else is rejected before touching the disk
+pub fn validate_capture_id(id: &str) -> Result<(), String> {
+    if uuid::Uuid::parse_str(id).is_err() {
+        return Err(format!("Identifiant de capture invalide: {}", id));
+    }
+    Ok(())
+}
+
+/// Lazily opened capture index shared by all commands
+#[derive(Default)]
+pub struct HistoryState(Mutex<Option<CaptureIndex>>);
+
+impl HistoryState {
+    /// Run `f` with the index, opening it in the app data directory on first use
+    pub fn with_index<R>(
+        &self,
+        app: &AppHandle,
+        f: impl FnOnce(&mut CaptureIndex) -> Result<R, String>,
+    ) -> Result<R, String> {
+        let mut guard = self.0.lock().unwrap();
+        if guard.is_none() {
+            let dir = app
+                .path()
+                .app_data_dir()
+                .map_err(|e| e.to_string())?
+                .join("captures");
+            *guard = Some(CaptureIndex::open(dir)?);
+        }
+
+        f(guard.as_mut().expect("index opened above"))
+    }
+}
+
+/// Shortcut for commands
+pub fn with_history<R>(
+    app: &AppHandle,
+    f: impl FnOnce(&mut CaptureIndex) -> Result<R, String>,
+) -> Result<R, String> {
+    app.state::<HistoryState>().with_index(app, f)
+}
+
+/// List recent captures
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+pub fn list_captures(app: AppHandle, limit: Option<usize>) -> Result<Vec<CaptureRecord>, String> {
+    let limit = limit.unwrap_or(DEFAULT_LIST_LIMIT);
+    with_history(&app, |index| Ok(index.list(limit)))
+}
+
+/// Search captures by OCR text
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+pub fn search_captures(app: AppHandle, query: String, limit: Option<usize>) -> Result<Vec<CaptureRecord>, String> {
+    if query.len() > MAX_QUERY_LEN {
+        return Err(format!("Requête trop longue (max {} caractères)", MAX_QUERY_LEN));
+    }
+    let limit = limit.unwrap_or(DEFAULT_LIST_LIMIT);
+    with_history(&app, |index| Ok(index.search(&query, limit)))
+}
+
+/// Thumbnail of a capture as a PNG data URL
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+pub fn get_capture_thumbnail(app: AppHandle, id: String, size: Option<u32>) -> Result<String, String> {
+    use base64::{Engine as _, engine::general_purpose};
+
+    validate_capture_id(&id)?;
+    let size = size.unwrap_or(DEFAULT_THUMBNAIL_SIZE);
+    if !(MIN_THUMBNAIL_SIZE..=MAX_THUMBNAIL_SIZE).contains(&size) {
+        return Err(format!("Taille de miniature invalide: {}", size));
+    }
+
+    let bytes = with_history(&app, |index| index.thumbnail(&id, size))?;
+    Ok(format!("data:image/png;base64,{}", general_purpose::STANDARD.encode(bytes)))
+}
+
+/// Delete a capture
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+pub fn delete_capture(app: AppHandle, id: String) -> Result<(), String> {
+    validate_capture_id(&id)?;
+    with_history(&app, |index| index.delete(&id).map(|_| ()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/ocr/invoice.png")
+    }
+
+    fn import(index: &mut CaptureIndex, scratch: &Path) -> CaptureRecord {
+        let path = scratch.join(format!("screenshot_{}.png", uuid::Uuid::new_v4()));
+        fs::copy(fixture(), &path).unwrap();
+        index
+            .import(NewCapture { path, display_id: 1, width: 480, height: 140, format: CaptureFormat::Png, window: None })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_import_persists_and_reloads() {
+        let dir = tempfile::tempdir().unwrap();
+        let scratch = tempfile::tempdir().unwrap();
+
+        let mut index = CaptureIndex::open(dir.path()).unwrap();
+        let record = import(&mut index, scratch.path());
+        assert!(record.path.starts_with(dir.path()));
+        assert!(record.path.exists());
+
+        index.set_ocr_text(&record.id, Some("Invoice 42".into())).unwrap();
+        index.link_conversation(&record.id, "conv-1").unwrap();
+
+        let reopened = CaptureIndex::open(dir.path()).unwrap();
+        let stored = reopened.get(&record.id).unwrap();
+        assert_eq!(stored.ocr_text.as_deref(), Some("Invoice 42"));
+        assert_eq!(stored.conversation_id.as_deref(), Some("conv-1"));
+        assert_eq!((stored.width, stored.height), (480, 140));
+    }
+
+    #[test]
+    fn test_corrupt_index_is_kept_aside() {
+        let dir = tempfile::tempdir().unwrap();
+        let scratch = tempfile::tempdir().unwrap();
+        let corrupt = b"[{\"id\": \"truncated";
+        fs::write(dir.path().join(INDEX_FILE), corrupt).unwrap();
+
+        let mut index = CaptureIndex::open(dir.path()).unwrap();
+        assert!(index.list(10).is_empty());
+        import(&mut index, scratch.path());
+
+        let kept: Vec<PathBuf> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.file_name().unwrap().to_string_lossy().starts_with("index.json.corrupt-"))
+            .collect();
+        assert_eq!(kept.len(), 1);
+        assert_eq!(fs::read(&kept[0]).unwrap(), corrupt);
+        assert_eq!(CaptureIndex::open(dir.path()).unwrap().list(10).len(), 1);
+    }
+
+    #[test]
+    fn test_list_and_search_newest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let scratch = tempfile::tempdir().unwrap();
+        let mut index = CaptureIndex::open(dir.path()).unwrap();
+
+        let first = import(&mut index, scratch.path());
+        let second = import(&mut index, scratch.path());
+        let third = import(&mut index, scratch.path());
+        index.set_ocr_text(&first.id, Some("Quarterly INVOICE".into())).unwrap();
+        index.set_ocr_text(&third.id, Some("invoice draft".into())).unwrap();
+
+        let listed: Vec<_> = index.list(10).into_iter().map(|r| r.id).collect();
+        assert_eq!(listed, vec![third.id.clone(), second.id.clone(), first.id.clone()]);
+
+        let found: Vec<_> = index.search("invoice", 10).into_iter().map(|r| r.id).collect();
+        assert_eq!(found, vec![third.id, first.id]);
+        assert!(index.search("nothing", 10).is_empty());
+    }
+
+    #[test]
+    fn test_thumbnail_and_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        let scratch = tempfile::tempdir().unwrap();
+        let mut index = CaptureIndex::open(dir.path()).unwrap();
+        let record = import(&mut index, scratch.path());
+
+        let bytes = index.thumbnail(&record.id, 64).unwrap();
+        let thumb = image::load_from_memory(&bytes).unwrap();
+        assert_eq!(thumb.width().max(thumb.height()), 64);
+
+        index.delete(&record.id).unwrap();
+        assert!(index.get(&record.id).is_none());
+        assert!(!record.path.exists());
+        assert_eq!(fs::read_dir(dir.path().join(THUMBNAILS_DIR)).unwrap().count(), 0);
+        assert!(index.delete(&record.id).is_err());
+    }
+
+    #[test]
+    fn test_capture_id_validation() {
+        assert!(validate_capture_id(&uuid::Uuid::new_v4().to_string()).is_ok());
+        assert!(validate_capture_id("../index.json").is_err());
+        assert!(validate_capture_id("").is_err());
+    }
+}
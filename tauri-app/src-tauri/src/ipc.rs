@@ -0,0 +1,590 @@
+// src-tauri/src/ipc.rs
+//! 🚦 IPC command middleware
+//!
+//! Features:
+//! - Single registry of every IPC command with its input type and rate class (`commands!`)
+//! - Window permission, rate limit and arguments checked in the invoke hook, before the command runs
+//! - A command cannot be handed to Tauri without declaring both
+//! - Test failing when a `#[tauri::command]` is missing from the registry or not instrumented
+//! - Argument sizes and rejections reported to `metrics`
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::Path;
+use tauri::ipc::{Invoke, InvokeBody};
+use tauri::Runtime;
+use tracing::warn;
+use crate::audit::{self, AuditFilter, AuditOutcome};
+use crate::{metrics, permissions};
+use crate::capture::CaptureSettings;
+use crate::consent::Consent;
+use crate::log_tail::LogTailFilter;
+use crate::logging::LogOutput;
+use crate::openai::{ChatRequest, ChatResponse, MAX_CONTEXT_CHARS, MAX_MESSAGE_CHARS};
+use crate::redaction::RedactionSettings;
+use crate::validation::{absolute_path, ValidatedInput, ValidationError, MAX_PATH_LEN};
+
+/// Longest chat answer accepted back for copying
+const MAX_RESPONSE_CHARS: usize = 100_000;
+
+/// Image files the frontend may point at
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp"];
+
+/// How a command is rate limited, limits live in `rate_limits.json` under `classes`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateClass {
+    /// Window placement, show/hide, dragging
+    Ui,
+    /// Read-only getters
+    Query,
+    /// Settings, permissions and deletions
+    Settings,
+    /// Screen, window, clipboard and file access
+    Capture,
+    /// Keychain access
+    Secret,
+    /// New chat requests
+    Chat,
+    /// The command calls `validate_and_rate_limit(_async)` itself
+    SelfLimited,
+}
+
+impl RateClass {
+    pub const ALL: &'static [RateClass] = &[
+        RateClass::Ui,
+        RateClass::Query,
+        RateClass::Settings,
+        RateClass::Capture,
+        RateClass::Secret,
+        RateClass::Chat,
+        RateClass::SelfLimited,
+    ];
+
+    /// Key in `rate_limits.json`, `None` when the hook does not rate limit
+    pub fn key(self) -> Option<&'static str> {
+        match self {
+            RateClass::Ui => Some("ui"),
+            RateClass::Query => Some("query"),
+            RateClass::Settings => Some("settings"),
+            RateClass::Capture => Some("capture"),
+            RateClass::Secret => Some("secret"),
+            RateClass::Chat => Some("chat"),
+            RateClass::SelfLimited => None,
+        }
+    }
+}
+
+/// Input type of a command: its JSON arguments, validated before the command runs
+pub trait CommandInput: DeserializeOwned + ValidatedInput {}
+
+impl<T: DeserializeOwned + ValidatedInput> CommandInput for T {}
+
+/// Registry entry for one command
+pub struct CommandSpec {
+    /// Path as written in the registry, e.g. `history::list_captures`
+    pub path: &'static str,
+    pub rate: RateClass,
+    validate: fn(&Value) -> Result<(), ValidationError>,
+}
+
+impl CommandSpec {
+    pub const fn new<T: CommandInput>(path: &'static str, rate: RateClass) -> Self {
+        Self { path, rate, validate: validate_args::<T> }
+    }
+
+    /// Name the frontend invokes
+    pub fn name(&self) -> &'static str {
+        self.path.rsplit("::").next().unwrap_or(self.path)
+    }
+
+    /// Deserialize and validate JSON arguments
+    pub fn validate_args(&self, args: &Value) -> Result<(), ValidationError> {
+        (self.validate)(args)
+    }
+
+    /// Rate limit then validate the raw invoke payload
+    pub fn check(&self, payload: &InvokeBody) -> Result<(), ValidationError> {
+        if let Some(class) = self.rate.key() {
+            crate::rate_limit::global().check_in(self.name(), Some(class))?;
+        }
+
+        match payload {
+            InvokeBody::Json(args) => self.validate_args(args),
+            InvokeBody::Raw(_) => Err(ValidationError::InvalidFormat {
+                field: "arguments".to_string(),
+                reason: "raw payloads are not accepted".to_string(),
+            }),
+        }
+    }
+}
+
+fn validate_args<T: CommandInput>(args: &Value) -> Result<(), ValidationError> {
+    let input = T::deserialize(args).map_err(|e| ValidationError::InvalidFormat {
+        field: "arguments".to_string(),
+        reason: e.to_string(),
+    })?;
+    input.validate()
+}
+
+/// Declare every IPC command once, with its input type and rate class.
+///
+/// Expands to `COMMANDS` (the registry) and `invoke_handler()` (the Tauri
+/// handler wrapped by the middleware). Commands left out of the list are
+/// not reachable from the frontend.
+macro_rules! commands {
+    ($($head:ident $(:: $tail:ident)* : $input:ty => $rate:ident),+ $(,)?) => {
+        pub(crate) static COMMANDS: &[$crate::ipc::CommandSpec] = &[
+            $($crate::ipc::CommandSpec::new::<$input>(
+                concat!(stringify!($head) $(, "::", stringify!($tail))*),
+                $crate::ipc::RateClass::$rate,
+            )),+
+        ];
+
+        fn invoke_handler<R: tauri::Runtime>() -> impl Fn(tauri::ipc::Invoke<R>) -> bool + Send + Sync + 'static {
+            let handler = tauri::generate_handler![$($head $(:: $tail)*),+];
+            move |invoke| $crate::ipc::dispatch(invoke, &handler)
+        }
+    };
+}
+pub(crate) use commands;
+
+/// Registry entry for `command`
+pub fn spec(command: &str) -> Option<&'static CommandSpec> {
+    crate::COMMANDS.iter().find(|spec| spec.name() == command)
+}
+
+pub fn command_names() -> impl Iterator<Item = &'static str> {
+    crate::COMMANDS.iter().map(CommandSpec::name)
+}
+
+/// Invoke hook: check window permission, rate limit and arguments before the command runs
+pub fn dispatch<R: Runtime>(invoke: Invoke<R>, handler: &dyn Fn(Invoke<R>) -> bool) -> bool {
+    let command = invoke.message.command();
+    let Some(spec) = spec(command) else {
+        return handler(invoke);
+    };
+
+    let window = invoke.message.webview().label().to_string();
+    let payload = invoke.message.payload();
+
+    // Denied calls do not consume rate limit tokens
+    let outcome = match permissions::check(&window, command) {
+        Err(e) => AuditOutcome::Denied { reason: e.to_string() },
+        Ok(()) => match spec.check(payload) {
+            Ok(()) => AuditOutcome::Admitted,
+            Err(e) => AuditOutcome::Rejected { reason: e.to_string() },
+        },
+    };
+
+    let args = match payload {
+        InvokeBody::Json(args) => args,
+        InvokeBody::Raw(_) => &Value::Null,
+    };
+
+    // Latency and outcome come from the command's own `ipc` span
+    match outcome {
+        AuditOutcome::Admitted => metrics::record_request(command, metrics::json_len(args)),
+        _ => metrics::record_rejection(command),
+    }
+
+    if audit::should_record(command, &outcome) {
+        audit::record(command, &window, outcome.clone(), args);
+    }
+
+    match outcome {
+        AuditOutcome::Denied { reason } | AuditOutcome::Rejected { reason } => {
+            warn!("🚦 Command '{}' from '{}' rejected: {}", command, window, reason);
+            invoke.resolver.reject(reason);
+            true
+        }
+        _ => handler(invoke),
+    }
+}
+
+// === Input types ===
+
+/// Commands taking no frontend arguments (app handle, state and window are injected)
+#[derive(Deserialize, Debug, ValidatedInput)]
+pub struct NoArgs {}
+
+/// `custom` rule: absolute path to an image file
+pub fn image_path(field: &str, value: &str) -> Result<(), ValidationError> {
+    absolute_path(field, value)?;
+
+    let extension = Path::new(value)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+    if !IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(ValidationError::InvalidFormat {
+            field: field.to_string(),
+            reason: "not an image file".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// `custom` rule: history capture id
+pub fn capture_id(field: &str, value: &str) -> Result<(), ValidationError> {
+    crate::history::validate_capture_id(value).map_err(|reason| ValidationError::InvalidFormat {
+        field: field.to_string(),
+        reason,
+    })
+}
+
+/// `custom` rule: crash report id, a UUID naming its file
+pub fn crash_id(field: &str, value: &str) -> Result<(), ValidationError> {
+    uuid::Uuid::parse_str(value).map(|_| ()).map_err(|_| ValidationError::InvalidFormat {
+        field: field.to_string(),
+        reason: "not a crash report id".to_string(),
+    })
+}
+
+/// `custom` rule: attachment file list
+pub fn attachment_paths(field: &str, value: &[String]) -> Result<(), ValidationError> {
+    if value.len() > crate::attachments::MAX_ATTACHMENTS {
+        return Err(ValidationError::InputTooLarge {
+            field: field.to_string(),
+            max_size: crate::attachments::MAX_ATTACHMENTS,
+        });
+    }
+    for path in value {
+        if path.trim().is_empty() {
+            return Err(ValidationError::EmptyField { field: field.to_string() });
+        }
+        absolute_path(field, path)?;
+    }
+    Ok(())
+}
+
+/// `custom` rule: chat answer sent back for copying
+pub fn chat_response(field: &str, value: &ChatResponse) -> Result<(), ValidationError> {
+    if value.message.len() > MAX_RESPONSE_CHARS {
+        return Err(ValidationError::InputTooLarge { field: field.to_string(), max_size: MAX_RESPONSE_CHARS });
+    }
+    Ok(())
+}
+
+/// `custom` rule: `EnvFilter` directives, e.g. `info,tauri_app_lib::openai=debug`
+pub fn log_filter(field: &str, value: &str) -> Result<(), ValidationError> {
+    tracing_subscriber::EnvFilter::try_new(value).map(|_| ()).map_err(|e| ValidationError::InvalidFormat {
+        field: field.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+#[derive(Deserialize, Debug, ValidatedInput)]
+#[serde(rename_all = "camelCase")]
+pub struct ImagePathArgs {
+    #[validate(non_empty, len(max = MAX_PATH_LEN), custom = "image_path")]
+    pub image_path: String,
+}
+
+#[derive(Deserialize, Debug, ValidatedInput)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactCaptureArgs {
+    #[validate(non_empty, len(max = MAX_PATH_LEN), custom = "image_path")]
+    pub image_path: String,
+    #[validate(non_empty, len(max = 32), charset = "alnum_-")]
+    pub provider: String,
+}
+
+#[derive(Deserialize, Debug, ValidatedInput)]
+pub struct StartChatArgs {
+    #[validate(non_empty, len(max = MAX_MESSAGE_CHARS), custom = "crate::openai::reject_prompt_injection")]
+    pub message: String,
+    #[validate(len(max = MAX_CONTEXT_CHARS))]
+    pub context: Option<String>,
+}
+
+#[derive(Deserialize, Debug, ValidatedInput)]
+pub struct AttachCaptureArgs {
+    #[validate(custom = "capture_id")]
+    pub id: String,
+    #[validate(non_empty, len(max = MAX_MESSAGE_CHARS), custom = "crate::openai::reject_prompt_injection")]
+    pub message: String,
+}
+
+#[derive(Deserialize, Debug, ValidatedInput)]
+pub struct ChatWithOpenAiArgs {
+    #[validate(nested)]
+    pub request: ChatRequest,
+}
+
+#[derive(Deserialize, Debug, ValidatedInput)]
+pub struct OpenAiKeyArgs {
+    #[validate(len(min = 20, max = 256), charset = "alnum_-")]
+    pub key: String,
+}
+
+#[derive(Deserialize, Debug, ValidatedInput)]
+pub struct CaptureSettingsArgs {
+    #[validate(nested)]
+    pub settings: CaptureSettings,
+}
+
+#[derive(Deserialize, Debug, ValidatedInput)]
+pub struct RedactionSettingsArgs {
+    #[validate(nested)]
+    pub settings: RedactionSettings,
+}
+
+#[derive(Deserialize, Debug, ValidatedInput)]
+pub struct ListCapturesArgs {
+    #[validate(range(1..=1000))]
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, Debug, ValidatedInput)]
+pub struct SearchCapturesArgs {
+    #[validate(len(max = crate::history::MAX_QUERY_LEN))]
+    pub query: String,
+    #[validate(range(1..=1000))]
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, Debug, ValidatedInput)]
+pub struct CaptureThumbnailArgs {
+    #[validate(custom = "capture_id")]
+    pub id: String,
+    #[validate(range(crate::history::MIN_THUMBNAIL_SIZE..=crate::history::MAX_THUMBNAIL_SIZE))]
+    pub size: Option<u32>,
+}
+
+#[derive(Deserialize, Debug, ValidatedInput)]
+pub struct CaptureIdArgs {
+    #[validate(custom = "capture_id")]
+    pub id: String,
+}
+
+#[derive(Deserialize, Debug, ValidatedInput)]
+pub struct CrashIdArgs {
+    #[validate(custom = "crash_id")]
+    pub id: String,
+}
+
+#[derive(Deserialize, Debug, ValidatedInput)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureWindowArgs {
+    pub window_id: u32,
+}
+
+#[derive(Deserialize, Debug, ValidatedInput)]
+pub struct ClipboardPermissionArgs {
+    pub allowed: bool,
+}
+
+#[derive(Deserialize, Debug, ValidatedInput)]
+pub struct ConsentArgs {
+    #[validate(nested)]
+    pub consent: Consent,
+}
+
+#[derive(Deserialize, Debug, ValidatedInput)]
+pub struct CopyAnswerArgs {
+    #[validate(custom = "chat_response")]
+    pub response: ChatResponse,
+}
+
+#[derive(Deserialize, Debug, ValidatedInput)]
+pub struct CopyCodeBlockArgs {
+    #[validate(custom = "chat_response")]
+    pub response: ChatResponse,
+    #[validate(range(1..=1000))]
+    pub index: usize,
+}
+
+#[derive(Deserialize, Debug, ValidatedInput)]
+pub struct AddAttachmentsArgs {
+    #[validate(custom = "attachment_paths")]
+    pub paths: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, ValidatedInput)]
+pub struct AttachmentIdArgs {
+    #[validate(non_empty, len(max = 64), charset = "alnum-")]
+    pub id: String,
+}
+
+#[derive(Deserialize, Debug, ValidatedInput)]
+pub struct QuotaArgs {
+    #[validate(non_empty, len(max = 64), charset = "alnum_")]
+    pub command: Option<String>,
+}
+
+#[derive(Deserialize, Debug, ValidatedInput)]
+pub struct CspContextArgs {
+    #[validate(non_empty, len(max = 32), charset = "alnum_-")]
+    pub context: String,
+}
+
+#[derive(Deserialize, Debug, ValidatedInput)]
+pub struct AuditLogArgs {
+    #[validate(nested)]
+    pub filter: Option<AuditFilter>,
+}
+
+#[derive(Deserialize, Debug, ValidatedInput)]
+pub struct ShortcutArgs {
+    #[validate(non_empty, len(max = 64))]
+    pub shortcut: String,
+}
+
+#[derive(Deserialize, Debug, ValidatedInput)]
+pub struct LogLevelArgs {
+    #[validate(non_empty, len(max = 512), custom = "log_filter")]
+    pub filter: String,
+    pub output: Option<LogOutput>,
+}
+
+#[derive(Deserialize, Debug, ValidatedInput)]
+pub struct LogTailArgs {
+    #[validate(nested)]
+    pub filter: Option<LogTailFilter>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashSet;
+
+    /// `fn` names following `#[tauri::command]` in every source file, with whether an `ipc` span times them
+    fn declared_commands() -> Vec<(String, bool)> {
+        let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        let mut commands = Vec::new();
+
+        for entry in std::fs::read_dir(src).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().and_then(|e| e.to_str()) != Some("rs") {
+                continue;
+            }
+            let source = std::fs::read_to_string(&path).unwrap();
+            let mut lines = source.lines();
+            while let Some(line) = lines.next() {
+                if line.trim() != "#[tauri::command]" {
+                    continue;
+                }
+                let mut instrumented = false;
+                let signature = lines
+                    .find(|l| {
+                        instrumented |= l.trim().starts_with(r#"#[tracing::instrument(target = "ipc""#);
+                        l.contains("fn ")
+                    })
+                    .unwrap();
+                let name = signature.split("fn ").nth(1).and_then(|rest| rest.split(['(', '<']).next());
+                commands.push((name.unwrap().trim().to_string(), instrumented));
+            }
+        }
+        commands
+    }
+
+    fn check(command: &str, args: Value) -> Result<(), ValidationError> {
+        let spec = spec(command).unwrap_or_else(|| panic!("{} is not registered", command));
+        spec.validate_args(&args)
+    }
+
+    #[test]
+    fn test_every_command_is_registered() {
+        let registered: HashSet<&str> = command_names().collect();
+        assert_eq!(registered.len(), crate::COMMANDS.len(), "duplicate command in the registry");
+
+        let declared = declared_commands();
+        assert!(!declared.is_empty());
+        for (command, _) in &declared {
+            assert!(
+                registered.contains(command.as_str()),
+                "#[tauri::command] `{}` must be declared in `commands!` with an input type and rate class",
+                command
+            );
+        }
+    }
+
+    #[test]
+    fn test_every_command_is_instrumented() {
+        for (command, instrumented) in declared_commands() {
+            assert!(
+                instrumented,
+                "#[tauri::command] `{}` needs `#[tracing::instrument(target = \"ipc\", skip_all)]` for the backend metrics",
+                command
+            );
+        }
+    }
+
+    #[test]
+    fn test_rate_classes_are_configured() {
+        let config = crate::rate_limit::RateLimitConfig::default();
+        for class in RateClass::ALL.iter().filter_map(|class| class.key()) {
+            assert!(config.classes.contains_key(class), "class '{}' missing from rate_limits.json", class);
+        }
+    }
+
+    #[test]
+    fn test_arguments_are_validated() {
+        assert!(check("capture_screen", json!({})).is_ok());
+        assert!(check("resize_window", json!({ "width": 800.0, "height": 600.0 })).is_ok());
+
+        // Resizes share the WindowSize bounds
+        for command in ["resize_window", "input_resize", "context_resize"] {
+            assert!(check(command, json!({ "width": 1.0, "height": 600.0 })).is_err(), "{}", command);
+        }
+
+        assert!(check("get_image_as_base64", json!({ "imagePath": "/tmp/shot.png" })).is_ok());
+        assert!(check("get_image_as_base64", json!({ "imagePath": "/tmp/../etc/passwd.png" })).is_err());
+        assert!(check("get_image_as_base64", json!({ "imagePath": "/etc/passwd" })).is_err());
+        assert!(check("get_image_as_base64", json!({ "imagePath": "shot.png" })).is_err());
+
+        assert!(check("delete_capture", json!({ "id": uuid::Uuid::new_v4().to_string() })).is_ok());
+        assert!(check("delete_capture", json!({ "id": "not-a-uuid" })).is_err());
+        assert!(check("get_capture_thumbnail", json!({ "id": uuid::Uuid::new_v4().to_string(), "size": 4 })).is_err());
+
+        let too_many: Vec<String> = (0..10).map(|i| format!("/tmp/{}.txt", i)).collect();
+        assert!(check("add_attachments", json!({ "paths": too_many })).is_err());
+        assert!(check("add_attachments", json!({ "paths": ["/tmp/notes.md"] })).is_ok());
+        assert!(check("add_attachments", json!({ "paths": ["notes.md"] })).is_err());
+        assert!(check("add_attachments", json!({ "paths": ["/tmp/../etc/shadow"] })).is_err());
+
+        assert!(check("start_chat", json!({ "message": "Explain this error" })).is_ok());
+        assert!(check("start_chat", json!({ "message": "" })).is_err());
+        assert!(check("secure_load", json!({ "key": "bad key!" })).is_err());
+        assert!(check("secure_store", json!({ "key": "github_token", "value": "ghp_a1b2;c3|d4&e5" })).is_ok());
+
+        assert!(check("get_audit_log", json!({})).is_ok());
+        assert!(check("get_audit_log", json!({ "filter": { "command": "secure_load", "outcome": "rejected" } })).is_ok());
+        assert!(check("get_audit_log", json!({ "filter": { "limit": 0 } })).is_err());
+
+        assert!(check("export_crash_report", json!({ "id": uuid::Uuid::new_v4().to_string() })).is_ok());
+        assert!(check("export_crash_report", json!({ "id": "../audit/audit" })).is_err());
+
+        assert!(check("set_consent", json!({ "consent": { "errorReporting": true, "metrics": false } })).is_ok());
+        assert!(check("set_consent", json!({ "consent": { "errorReporting": "yes" } })).is_err());
+        assert!(check("set_consent", json!({})).is_err());
+
+        assert!(check("set_log_level", json!({ "filter": "info,tauri_app_lib::openai=debug" })).is_ok());
+        assert!(check("set_log_level", json!({ "filter": "debug", "output": "files" })).is_ok());
+        assert!(check("set_log_level", json!({ "filter": "debug", "output": "network" })).is_err());
+        assert!(check("set_log_level", json!({ "filter": "info,[{" })).is_err());
+        assert!(check("subscribe_logs", json!({})).is_ok());
+        assert!(check("subscribe_logs", json!({ "filter": { "level": "warn", "target": "tauri_app_lib::ocr" } })).is_ok());
+        assert!(check("subscribe_logs", json!({ "filter": { "level": "verbose" } })).is_err());
+        assert!(check("subscribe_logs", json!({ "filter": { "target": "" } })).is_err());
+    }
+
+    #[test]
+    fn test_malformed_arguments_are_rejected() {
+        let err = check("capture_window", json!({ "windowId": "twelve" })).unwrap_err();
+        assert!(matches!(err, ValidationError::InvalidFormat { .. }));
+        assert!(check("secure_store", json!({ "key": "api" })).is_err());
+        assert!(check("set_clipboard_permission", json!({})).is_err());
+    }
+
+    #[test]
+    fn test_names_match_frontend_commands() {
+        assert_eq!(spec("list_captures").unwrap().path, "history::list_captures");
+        assert_eq!(spec("list_captures").unwrap().rate, RateClass::Query);
+        assert_eq!(spec("chat_with_openai").unwrap().rate, RateClass::SelfLimited);
+        assert!(spec("unknown_command").is_none());
+    }
+}
@@ -3,14 +3,53 @@
 mod stealth;
 mod platform;
 mod logging;
+mod log_tail;
 mod validation;
+mod rate_limit;
 mod csp_manager;
 mod openai;
 mod ns_panel;
+mod capture;
+mod ocr;
+mod redaction;
+mod history;
+mod window_capture;
+mod clipboard;
+mod attachments;
+mod ipc;
+mod audit;
+mod permissions;
+mod crash;
+mod crash_upload;
+mod consent;
+mod metrics;
+mod diagnostics;
+#[cfg(feature = "otel")]
+mod otel;
+// 📡 Utilisé par le streaming des réponses (phase 2)
+#[cfg_attr(not(any(test, fuzzing)), allow(dead_code))]
+mod sse;
 #[cfg(test)]
 mod tests;
 
-use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder, WebviewWindow, Listener};
+/// 🐛 Points d'entrée des cibles `cargo fuzz` (voir fuzz/)
+#[cfg(fuzzing)]
+pub mod fuzzing {
+    pub use crate::openai::ChatRequest;
+    pub use crate::sse::{SseEvent, SseParser};
+    pub use crate::validation::{absolute_path, SecureKey, SecureKeyValue, ValidatedInput, WindowSize, MAX_SECRET_BYTES};
+
+    /// Validation du middleware IPC, `None` si la commande n'existe pas
+    pub fn validate_ipc_args(command: &str, args: &serde_json::Value) -> Option<bool> {
+        crate::ipc::spec(command).map(|spec| spec.validate_args(args).is_ok())
+    }
+
+    pub fn ipc_commands() -> impl Iterator<Item = &'static str> {
+        crate::ipc::command_names()
+    }
+}
+
+use tauri::{AppHandle, Emitter, Manager, Runtime, WebviewUrl, WebviewWindowBuilder, WebviewWindow, Listener};
 use tracing::{info, warn, error, debug};
 use std::sync::{Arc, Mutex};
 use std::collections::HashMap;
@@ -57,10 +96,7 @@ impl Default for InputWindowState {
 pub type InputState = Arc<Mutex<InputWindowState>>;
 
 
-fn capture_screen_internal() -> Result<String, String> {
-    use std::fs;
-    use std::env;
-
+fn capture_screen_internal() -> Result<history::NewCapture, String> {
     // Capturer l'écran principal
     let screens = screenshots::Screen::all().map_err(|e| e.to_string())?;
     let screen = screens.first().ok_or("Aucun écran trouvé")?;
@@ -68,66 +104,128 @@ fn capture_screen_internal() -> Result<String, String> {
     // Capturer l'écran complet
     let image = screen.capture().map_err(|e| e.to_string())?;
 
-    // Créer le dossier de sauvegarde dans le répertoire temporaire
-    let temp_dir = env::temp_dir();
-    let screenshots_dir = temp_dir.join("tauri-screenshots");
-    fs::create_dir_all(&screenshots_dir).map_err(|e| e.to_string())?;
+    // Encoder selon les options de capture (format, qualité, taille max)
+    let encoded = capture::encode(&image, &capture::current_settings().capture)?;
 
-    // Générer un nom de fichier unique
-    let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
-    let filename = format!("screenshot_{}.png", timestamp);
-    let file_path = screenshots_dir.join(filename);
+    // Sauvegarder l'image dans le répertoire temporaire
+    let file_path = capture::save_to_temp(&encoded, "screenshot")?;
 
-    // Sauvegarder l'image
-    image.save(&file_path).map_err(|e| e.to_string())?;
+    Ok(history::NewCapture {
+        path: file_path,
+        display_id: screen.display_info.id,
+        width: encoded.width,
+        height: encoded.height,
+        format: encoded.format,
+        window: None,
+    })
+}
 
-    Ok(file_path.to_string_lossy().to_string())
+/// Capture l'écran et l'enregistre dans l'historique
+fn capture_to_history(app: &AppHandle) -> Result<history::CaptureRecord, String> {
+    let captured = capture_screen_internal()?;
+    history::with_history(app, |index| index.import(captured))
 }
 
 #[tauri::command]
-fn capture_screen() -> Result<String, String> {
-    capture_screen_internal()
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+fn capture_screen(app: AppHandle) -> Result<String, String> {
+    let record = capture_to_history(&app)?;
+    Ok(record.path.to_string_lossy().to_string())
 }
 
 #[tauri::command]
-fn capture_and_analyze() -> String {
-    match capture_screen_internal() {
-        Ok(image_path) => format!("Capture réussie: {}", image_path),
-        Err(e) => format!("Erreur de capture: {}", e),
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+async fn capture_and_analyze(app: AppHandle) -> Result<ocr::CaptureAnalysis, String> {
+    let record = capture_to_history(&app)?;
+    let image_path = record.path.to_string_lossy().to_string();
+    let recognizer = app.state::<ocr::OcrState>().recognizer(&app);
+
+    // OCR local (aucun appel réseau), hors du thread principal
+    let mut analysis = tauri::async_runtime::spawn_blocking(move || ocr::analyze_capture(recognizer, image_path))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Indexer le texte OCR pour la recherche dans l'historique
+    let text = Some(analysis.text.clone());
+    if let Err(e) = history::with_history(&app, |index| index.set_ocr_text(&record.id, text)) {
+        warn!("Texte OCR non indexé: {}", e);
     }
+    analysis.capture_id = Some(record.id);
+
+    Ok(analysis)
 }
 
 #[tauri::command]
-fn get_image_as_base64(image_path: String) -> Result<String, String> {
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+async fn get_image_as_base64(app: AppHandle, image_path: String) -> Result<String, String> {
+    let recognizer = app.state::<ocr::OcrState>().recognizer(&app);
+
+    // OCR + flou hors du thread principal
+    tauri::async_runtime::spawn_blocking(move || {
+        let recognizer = recognizer.as_deref().map_err(|e| e.to_string());
+        image_as_base64(&image_path, &redaction::current_settings(), recognizer)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Image prête pour l'upload vision : zones sensibles floutées, puis ré-encodée
+fn image_as_base64(
+    image_path: &str,
+    settings: &redaction::RedactionSettings,
+    recognizer: Result<&dyn ocr::TextRecognizer, String>,
+) -> Result<String, String> {
     use std::fs;
     use base64::{Engine as _, engine::general_purpose};
 
     // Lire le fichier image
-    let image_data = fs::read(&image_path).map_err(|e| format!("Erreur de lecture: {}", e))?;
+    let image_data = fs::read(image_path).map_err(|e| format!("Erreur de lecture: {}", e))?;
+
+    // 🙈 Flouter les zones sensibles puis ré-encoder (downscale + compression)
+    let (bytes, mime_type) = match image::load_from_memory(&image_data) {
+        Ok(decoded) => {
+            let mut image = decoded.to_rgba8();
+            redaction::redact_image_with(settings, openai::PROVIDER, recognizer, &mut image)?;
+            let encoded = capture::encode_for_vision(&image)?;
+            (encoded.bytes, encoded.format.mime_type())
+        }
+        // Une image illisible ne peut pas être analysée : envoyée telle quelle seulement sans redaction
+        Err(e) if settings.is_enabled_for(openai::PROVIDER) && settings.blur_images => {
+            return Err(format!("Erreur de décodage: {}", e));
+        }
+        Err(e) => {
+            warn!("Image non ré-encodée ({}), envoi du fichier original", e);
+
+            // Déterminer le type MIME basé sur l'extension
+            let mime_type = if image_path.ends_with(".png") {
+                "image/png"
+            } else if image_path.ends_with(".jpg") || image_path.ends_with(".jpeg") {
+                "image/jpeg"
+            } else if image_path.ends_with(".webp") {
+                "image/webp"
+            } else {
+                "image/png" // par défaut
+            };
+            (image_data, mime_type)
+        }
+    };
 
     // Convertir en base64
-    let base64_string = general_purpose::STANDARD.encode(&image_data);
-
-    // Déterminer le type MIME basé sur l'extension
-    let mime_type = if image_path.ends_with(".png") {
-        "image/png"
-    } else if image_path.ends_with(".jpg") || image_path.ends_with(".jpeg") {
-        "image/jpeg"
-    } else {
-        "image/png" // par défaut
-    };
+    let base64_string = general_purpose::STANDARD.encode(&bytes);
 
     // Retourner l'URL data
     Ok(format!("data:{};base64,{}", mime_type, base64_string))
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
 fn close_all_windows(app: AppHandle) -> tauri::Result<()> {
     app.exit(0);
     Ok(())
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
 fn start_window_dragging(app: AppHandle) -> tauri::Result<()> {
     if let Some(window) = app.get_webview_window("hud") {
         window.start_dragging()?;
@@ -144,6 +242,7 @@ fn start_window_dragging(app: AppHandle) -> tauri::Result<()> {
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
 fn force_hud_redraw(app: AppHandle) -> tauri::Result<()> {
     if let Some(window) = app.get_webview_window("hud") {
         // 🔧 Force complete window redraw to eliminate ghost images on macOS
@@ -159,12 +258,13 @@ fn force_hud_redraw(app: AppHandle) -> tauri::Result<()> {
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
 fn resize_window(app: AppHandle, width: f64, height: f64) -> Result<(), String> {
-    use validation::{validate_and_rate_limit, WindowSize};
+    use validation::{validate_input, WindowSize};
 
     let size = WindowSize { width, height };
 
-    validate_and_rate_limit("resize_window", size, |validated_size| {
+    validate_input("resize_window", size, |validated_size| {
         if let Some(window) = app.get_webview_window("hud") {
             window.set_size(tauri::Size::Logical(tauri::LogicalSize {
                 width: validated_size.width,
@@ -268,16 +368,16 @@ fn arrange_hud_children(app: &tauri::AppHandle) -> tauri::Result<()> {
         (s.width as i32, s.height as i32)
     } else { (0, 0) };
 
-    println!("🎯 Layout Manager - HUD: {:?}, Screen: {:?}", hud_r, screen);
-    println!("🎯 Layout Manager - Input: visible={}, size={:?}", input_vis, input_size);
-    println!("🎯 Layout Manager - Context: visible={}, size={:?}", context_vis, context_size);
+    debug!("🎯 Layout Manager - HUD: {:?}, Screen: {:?}", hud_r, screen);
+    debug!("🎯 Layout Manager - Input: visible={}, size={:?}", input_vis, input_size);
+    debug!("🎯 Layout Manager - Context: visible={}, size={:?}", context_vis, context_size);
 
     match (input_vis, context_vis) {
         (true, false) => {
             // Uniquement input → centré sous HUD
             if let Some(w) = &input {
                 let x = center_x(input_size.0);
-                println!("🎯 Layout: Input seul, centré à ({}, {})", x, line_y);
+                debug!("🎯 Layout: Input seul, centré à ({}, {})", x, line_y);
                 set_pos(w, x, line_y)?;
             }
         }
@@ -285,7 +385,7 @@ fn arrange_hud_children(app: &tauri::AppHandle) -> tauri::Result<()> {
             // Uniquement context → centré sous HUD
             if let Some(w) = &context {
                 let x = center_x(context_size.0);
-                println!("🎯 Layout: Context seul, centré à ({}, {})", x, line_y);
+                debug!("🎯 Layout: Context seul, centré à ({}, {})", x, line_y);
                 set_pos(w, x, line_y)?;
             }
         }
@@ -302,7 +402,7 @@ fn arrange_hud_children(app: &tauri::AppHandle) -> tauri::Result<()> {
                 let left_x = center_x(total_w);
                 let right_x = left_x + context_size.0 + GAP;
                 
-                println!("🎯 Layout: Côte à côte - Context à ({}, {}), Input à ({}, {})", left_x, line_y, right_x, line_y);
+                debug!("🎯 Layout: Côte à côte - Context à ({}, {}), Input à ({}, {})", left_x, line_y, right_x, line_y);
                 if let Some(wc) = &context { set_pos(wc, left_x, line_y)?; }
                 if let Some(wi) = &input { set_pos(wi, right_x, line_y)?; }
             } else if available_w > (GAP + 200 + 200) { // Au moins 200px chacune + gap
@@ -314,7 +414,7 @@ fn arrange_hud_children(app: &tauri::AppHandle) -> tauri::Result<()> {
                 let left_x = center_x(available_w);
                 let right_x = left_x + new_context_w + GAP;
                 
-                println!("🎯 Layout: Côte à côte adaptatif - Context à ({}, {}) [{}px], Input à ({}, {}) [{}px]", 
+                debug!("🎯 Layout: Côte à côte adaptatif - Context à ({}, {}) [{}px], Input à ({}, {}) [{}px]", 
                          left_x, line_y, new_context_w, right_x, line_y, new_input_w);
                 
                 // Ajuster les tailles temporairement
@@ -337,7 +437,7 @@ fn arrange_hud_children(app: &tauri::AppHandle) -> tauri::Result<()> {
                 let overflow = max_bottom - (screen.y + screen.h);
                 let adjust = if overflow > 0 { overflow } else { 0 };
 
-                println!("🎯 Layout: Pile verticale - Input à ({}, {}), Context à ({}, {}), adjust={}", 
+                debug!("🎯 Layout: Pile verticale - Input à ({}, {}), Context à ({}, {}), adjust={}", 
                          input_x, line_y - adjust, context_x, context_y - adjust, adjust);
                 if let Some(wi) = &input { set_pos(wi, input_x, line_y - adjust)?; }
                 if let Some(wc) = &context { set_pos(wc, context_x, context_y - adjust)?; }
@@ -383,12 +483,13 @@ impl DraggableWindowConfig {
     }
 }
 
-fn ensure_draggable_window(app: &AppHandle, window_name: &str, config: DraggableWindowConfig) -> tauri::Result<WebviewWindow> {
-    if let Some(w) = app.get_webview_window(window_name) {
-        return Ok(w);
-    }
-
-    let window = WebviewWindowBuilder::new(
+/// Fenêtre draggable avec sa CSP, prête à être construite
+fn draggable_window_builder<'a, R: Runtime>(
+    app: &'a AppHandle<R>,
+    window_name: &str,
+    config: &DraggableWindowConfig,
+) -> WebviewWindowBuilder<'a, R, AppHandle<R>> {
+    let builder = WebviewWindowBuilder::new(
         app,
         window_name,
         WebviewUrl::External(format!("http://localhost:1420/{}", config.route).parse().unwrap()),
@@ -402,8 +503,16 @@ fn ensure_draggable_window(app: &AppHandle, window_name: &str, config: Draggable
     .skip_taskbar(true)
     .inner_size(config.width, config.height)
     .position(config.initial_x, config.initial_y)
-    .visible(false)
-    .build()?;
+    .visible(false);
+    csp_manager::with_csp(app, window_name, builder)
+}
+
+fn ensure_draggable_window(app: &AppHandle, window_name: &str, config: DraggableWindowConfig) -> tauri::Result<WebviewWindow> {
+    if let Some(w) = app.get_webview_window(window_name) {
+        return Ok(w);
+    }
+
+    let window = draggable_window_builder(app, window_name, &config).build()?;
 
     // Style macOS commun
     #[cfg(all(target_os = "macos", feature = "stealth_macos"))]
@@ -452,7 +561,7 @@ fn show_draggable_window(app: &AppHandle, window_name: &str, config: DraggableWi
 fn undock_draggable_window(app: &AppHandle, window_name: &str) -> tauri::Result<()> {
     if let Some(window) = app.get_webview_window(window_name) {
         if let Some(hud) = app.get_webview_window("hud") {
-            println!("🔓 Undocking {} - passage en mode libre", window_name);
+            info!("🔓 Undocking {} - passage en mode libre", window_name);
 
             #[cfg(all(target_os = "macos", feature = "stealth_macos"))]
             unsafe {
@@ -469,7 +578,7 @@ fn undock_draggable_window(app: &AppHandle, window_name: &str) -> tauri::Result<
 
             // Émettre l'événement
             let _ = app.emit_to(window_name, &format!("{}:undocked", window_name), serde_json::json!({}));
-            println!("✅ {} undocked", window_name);
+            info!("✅ {} undocked", window_name);
         }
     }
     Ok(())
@@ -478,7 +587,7 @@ fn undock_draggable_window(app: &AppHandle, window_name: &str) -> tauri::Result<
 fn dock_draggable_window(app: &AppHandle, window_name: &str, config: DraggableWindowConfig) -> tauri::Result<()> {
     if let Some(window) = app.get_webview_window(window_name) {
         if let Some(hud) = app.get_webview_window("hud") {
-            println!("🔒 Docking {} to HUD", window_name);
+            info!("🔒 Docking {} to HUD", window_name);
 
             #[cfg(all(target_os = "macos", feature = "stealth_macos"))]
             unsafe {
@@ -495,7 +604,7 @@ fn dock_draggable_window(app: &AppHandle, window_name: &str, config: DraggableWi
 
             // Émettre l'événement
             let _ = app.emit_to(window_name, &format!("{}:docked", window_name), serde_json::json!({}));
-            println!("✅ {} docked", window_name);
+            info!("✅ {} docked", window_name);
         }
     }
     Ok(())
@@ -503,7 +612,7 @@ fn dock_draggable_window(app: &AppHandle, window_name: &str, config: DraggableWi
 
 fn start_dragging_window(app: &AppHandle, window_name: &str) -> tauri::Result<()> {
     if let Some(window) = app.get_webview_window(window_name) {
-        println!("🎯 start_{}_dragging appelé", window_name);
+        debug!("🎯 start_{}_dragging appelé", window_name);
 
         #[cfg(all(target_os = "macos", feature = "stealth_macos"))]
         unsafe {
@@ -513,8 +622,8 @@ fn start_dragging_window(app: &AppHandle, window_name: &str) -> tauri::Result<()
         }
 
         match window.start_dragging() {
-            Ok(()) => println!("✅ start_dragging OK for {}", window_name),
-            Err(e) => println!("❌ start_dragging failed for {}: {}", window_name, e),
+            Ok(()) => debug!("✅ start_dragging OK for {}", window_name),
+            Err(e) => warn!("❌ start_dragging failed for {}: {}", window_name, e),
         }
 
         #[cfg(all(target_os = "macos", feature = "stealth_macos"))]
@@ -554,7 +663,7 @@ fn check_snap_distance_for_window(app: &AppHandle, window_name: &str) -> tauri::
                 win_bottom >= hud_top &&
                 win_top <= hud_bottom;
 
-            println!(
+            debug!(
                 "🎯 {}/HUD overlap: {}=({}, {} -> {}, {}), hud=({}, {} -> {}, {}), should_snap={}",
                 window_name, window_name, win_left, win_top, win_right, win_bottom, hud_left, hud_top, hud_right, hud_bottom, is_in_snap_zone
             );
@@ -625,20 +734,160 @@ fn configure_panel_stealth(panel: &tauri::WebviewWindow, stealth: bool) -> tauri
     Ok(())
 }
 
-fn ensure_panel(app: &AppHandle) -> tauri::Result<WebviewWindow> {
-    if let Some(w) = app.get_webview_window("panel") {
-        return Ok(w); // déjà créé
+/// HUD avec sa CSP, d'après tauri.conf.json (`None` si la config n'a pas de fenêtre "hud")
+fn hud_builder<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<Option<WebviewWindowBuilder<'_, R, AppHandle<R>>>> {
+    let Some(config) = app.config().app.windows.iter().find(|w| w.label == "hud").cloned() else {
+        return Ok(None);
+    };
+    let builder = WebviewWindowBuilder::from_config(app, &config)?;
+    Ok(Some(csp_manager::with_csp(app, "hud", builder)))
+}
+
+/// Reconstruit un HUD détruit, NSPanel compris
+fn rebuild_hud(app: &AppHandle) -> tauri::Result<Option<WebviewWindow>> {
+    let Some(builder) = hud_builder(app)? else {
+        return Ok(None);
+    };
+    let hud = builder.build()?;
+
+    #[cfg(all(target_os = "macos", feature = "stealth_macos"))]
+    ns_panel::replace_ns_panel(app, &hud);
+
+    Ok(Some(hud))
+}
+
+/// Fenêtre ouverte à reconstruire : position et visibilité à restaurer
+struct OpenWindow {
+    label: String,
+    position: Option<tauri::PhysicalPosition<i32>>,
+    visible: bool,
+}
+
+fn rebuild_window(app: &AppHandle, open: &OpenWindow) -> tauri::Result<()> {
+    let window = match open.label.as_str() {
+        "hud" => rebuild_hud(app)?,
+        "panel" => Some(ensure_panel(app)?),
+        "input" => Some(ensure_draggable_window(app, "input", DraggableWindowConfig::for_input())?),
+        "context" => Some(ensure_draggable_window(app, "context", DraggableWindowConfig::for_context())?),
+        _ => None,
+    };
+    let Some(window) = window else {
+        return Ok(());
+    };
+
+    #[cfg(all(target_os = "macos", feature = "stealth_macos"))]
+    match open.label.as_str() {
+        "input" => ns_panel::init_input_ns_panel(app.clone(), window.clone()),
+        "context" => ns_panel::init_context_ns_panel(app.clone(), window.clone()),
+        _ => {}
     }
 
-    let hud = app.get_webview_window("hud").ok_or_else(|| tauri::Error::from(std::io::Error::new(std::io::ErrorKind::NotFound, "HUD window not found")))?;
-    let parent_ptr = hud.ns_window()?;
+    if let Some(position) = open.position {
+        window.set_position(position)?;
+    }
+    apply_current_stealth(app, &window)?;
+    if open.visible {
+        window.show()?;
+    }
+    Ok(())
+}
 
-    let panel = WebviewWindowBuilder::new(
+/// 🛡️ Détruit puis reconstruit les fenêtres dont la CSP doit être assouplie
+///
+/// Une CSP `<meta>` ne peut pas être relâchée dans une page chargée et les scripts
+/// d'initialisation sont figés à la construction : seule une nouvelle fenêtre
+/// (repassée par `with_csp`) reçoit la politique courante. Le panel est un enfant
+/// du HUD : il suit le HUD, détruit avant lui et reconstruit après lui.
+pub(crate) fn rebuild_windows(app: &AppHandle, labels: &[String]) {
+    let mut labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+    if labels.contains(&"hud") && !labels.contains(&"panel") && app.get_webview_window("panel").is_some() {
+        labels.push("panel");
+    }
+    // Ordre de destruction : enfants d'abord ; la reconstruction se fait dans l'ordre inverse
+    labels.sort_by_key(|label| match *label {
+        "panel" => 0,
+        "hud" => 2,
+        _ => 1,
+    });
+
+    let windows: Vec<(WebviewWindow, OpenWindow)> = labels
+        .into_iter()
+        .filter_map(|label| app.get_webview_window(label))
+        .map(|window| {
+            let open = OpenWindow {
+                label: window.label().to_string(),
+                position: window.outer_position().ok(),
+                visible: window.is_visible().unwrap_or(false),
+            };
+            (window, open)
+        })
+        .collect();
+    if windows.is_empty() {
+        return;
+    }
+
+    // Les labels ne sont libérés qu'à la réception de `Destroyed` : on attend la dernière
+    // fenêtre puis on reconstruit tout depuis la boucle principale
+    let (windows, reopen): (Vec<_>, Vec<_>) = windows.into_iter().unzip();
+    let pending = Arc::new(PendingRebuild {
+        remaining: std::sync::atomic::AtomicUsize::new(windows.len()),
+        reopen: Mutex::new(Some(reopen)),
+    });
+
+    for window in &windows {
+        let app = app.clone();
+        let pending = pending.clone();
+        window.on_window_event(move |event| {
+            if matches!(event, tauri::WindowEvent::Destroyed) {
+                pending.released(&app);
+            }
+        });
+    }
+
+    for window in windows {
+        if let Err(e) = window.destroy() {
+            error!("Failed to destroy window {}: {}", window.label(), e);
+            pending.released(app);
+        }
+    }
+}
+
+/// Fenêtres en cours de destruction avant leur reconstruction
+struct PendingRebuild {
+    remaining: std::sync::atomic::AtomicUsize,
+    reopen: Mutex<Option<Vec<OpenWindow>>>,
+}
+
+impl PendingRebuild {
+    fn released(&self, app: &AppHandle) {
+        if self.remaining.fetch_sub(1, std::sync::atomic::Ordering::SeqCst) != 1 {
+            return;
+        }
+        let Some(reopen) = self.reopen.lock().unwrap().take() else {
+            return;
+        };
+        let handle = app.clone();
+        let scheduled = app.run_on_main_thread(move || {
+            for open in reopen.iter().rev() {
+                match rebuild_window(&handle, open) {
+                    Ok(()) => info!("🛡️ Fenêtre {} reconstruite avec la CSP courante", open.label),
+                    Err(e) => error!("Failed to rebuild window {}: {}", open.label, e),
+                }
+            }
+        });
+        if let Err(e) = scheduled {
+            error!("Failed to schedule window rebuild: {}", e);
+        }
+    }
+}
+
+/// Panel avec sa CSP, prêt à être construit (le parent est ajouté par `ensure_panel`)
+fn panel_builder<R: Runtime>(app: &AppHandle<R>) -> WebviewWindowBuilder<'_, R, AppHandle<R>> {
+    let builder = WebviewWindowBuilder::new(
         app,
         "panel",
         WebviewUrl::External("http://localhost:1420/#/panel".parse().unwrap()),
     )
-    .parent_raw(parent_ptr)                 // 🔑 Child window du HUD
     .decorations(false)
     .transparent(true)
     .always_on_top(true)
@@ -648,8 +897,21 @@ fn ensure_panel(app: &AppHandle) -> tauri::Result<WebviewWindow> {
     .skip_taskbar(true)
     .inner_size(PANEL_WIDTH, PANEL_HEIGHT)
     .position(PANEL_INITIAL_X, PANEL_INITIAL_Y)
-    .visible(false)
-    .build()?;
+    .visible(false);
+    csp_manager::with_csp(app, "panel", builder)
+}
+
+fn ensure_panel(app: &AppHandle) -> tauri::Result<WebviewWindow> {
+    if let Some(w) = app.get_webview_window("panel") {
+        return Ok(w); // déjà créé
+    }
+
+    let hud = app.get_webview_window("hud").ok_or_else(|| tauri::Error::from(std::io::Error::new(std::io::ErrorKind::NotFound, "HUD window not found")))?;
+    let parent_ptr = hud.ns_window()?;
+
+    let panel = panel_builder(app)
+        .parent_raw(parent_ptr)                 // 🔑 Child window du HUD
+        .build()?;
 
     // 🔑 Supprimer complètement les contours de la fenêtre sur macOS
     #[cfg(all(target_os = "macos", feature = "stealth_macos"))]
@@ -670,7 +932,7 @@ fn ensure_panel(app: &AppHandle) -> tauri::Result<WebviewWindow> {
 
         // 3) Appliquer le mode furtif si il est actif
         if app.state::<stealth::StealthState>().is_active() {
-            println!("🔒 Applying stealth to newly created panel");
+            info!("🔒 Applying stealth to newly created panel");
             let _: () = msg_send![panel_ns, setSharingType: 0u64];
         }
     }
@@ -678,6 +940,7 @@ fn ensure_panel(app: &AppHandle) -> tauri::Result<WebviewWindow> {
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
 fn panel_show(app: AppHandle) -> tauri::Result<()> {
     ensure_panel(&app)?;
     let hud = app.get_webview_window("hud").ok_or_else(|| tauri::Error::from(std::io::Error::new(std::io::ErrorKind::NotFound, "HUD window not found")))?;
@@ -721,8 +984,8 @@ fn panel_show(app: AppHandle) -> tauri::Result<()> {
     let panel_x = target_x_phys_global;
     let panel_y = target_y_phys_global;
 
-    println!("🎯 Scale: {}, HUD logique: ({:.1}, {:.1}), Panel logique: ({:.1}, {:.1})", scale, hud_pos_log.x, hud_pos_log.y, target_x_log, target_y_log);
-    println!("🎯 Position finale physique: Panel=({}, {})", panel_x, panel_y);
+    debug!("🎯 Scale: {}, HUD logique: ({:.1}, {:.1}), Panel logique: ({:.1}, {:.1})", scale, hud_pos_log.x, hud_pos_log.y, target_x_log, target_y_log);
+    debug!("🎯 Position finale physique: Panel=({}, {})", panel_x, panel_y);
 
     // 🔑 CHANGEMENT CRUCIAL : Utiliser PhysicalPosition au lieu de LogicalPosition
     panel.set_position(tauri::PhysicalPosition::new(panel_x, panel_y)).map_err(|e| tauri::Error::from(std::io::Error::new(std::io::ErrorKind::Other, format!("Failed to set panel position: {}", e))))?;
@@ -746,6 +1009,7 @@ fn panel_show(app: AppHandle) -> tauri::Result<()> {
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
 fn panel_hide(app: AppHandle) -> tauri::Result<()> {
     if let Some(panel) = app.get_webview_window("panel") {
         // 1) Rendre invisible et non-interactif
@@ -769,14 +1033,52 @@ fn panel_hide(app: AppHandle) -> tauri::Result<()> {
 
 
 #[tauri::command]
-fn start_chat(app: AppHandle, message: String) -> tauri::Result<()> {
-    println!("🚀 start_chat called with message: {}", message);
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+fn start_chat(app: AppHandle, message: String, context: Option<String>) -> Result<(), String> {
+    info!(body = %message, "🚀 start_chat called");
+    spawn_chat(app, message, context, None);
+    Ok(())
+}
+
+/// Démarre une nouvelle conversation à partir d'une capture de l'historique
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+fn attach_capture_to_chat(app: AppHandle, id: String, message: String) -> Result<String, String> {
+    history::validate_capture_id(&id)?;
+
+    // L'id de conversation est créé ici pour être lié à la capture avant l'envoi
+    let conversation_id = uuid::Uuid::new_v4().to_string();
+    let context = history::with_history(&app, |index| {
+        index.link_conversation(&id, &conversation_id)?;
+        Ok(index
+            .get(&id)
+            .and_then(|record| record.ocr_text.as_deref())
+            .and_then(|text| ocr::text_to_context(text.lines(), ocr::DEFAULT_CONTEXT_CHARS)))
+    })?;
+
+    info!("🗂️ Capture {} attachée à la conversation {}", id, conversation_id);
+    spawn_chat(app, message, context, Some(conversation_id.clone()));
+    Ok(conversation_id)
+}
+
+fn spawn_chat(app: AppHandle, message: String, context: Option<String>, conversation_id: Option<String>) {
+    // 📎 Joindre les fichiers en attente dans la place laissée par le contexte écran
+    let budget = attachments::budget_after(context.as_deref());
+    let (attached_ids, attached) = match app.state::<attachments::AttachmentState>().pending_context(budget) {
+        Ok(pending) => pending,
+        Err(e) => {
+            warn!("📎 {}", e);
+            let _ = app.emit_to("input", "chat:error", serde_json::json!({ "error": e }));
+            return;
+        }
+    };
+    let context = attachments::join_context(context, attached);
 
     // 🎯 NOUVEAU : Ne plus afficher ResponsePage, l'InputPage gère tout maintenant
-    println!("🚀 Envoi direct vers InputPage (pas de ResponsePage)");
+    debug!("🚀 Envoi direct vers InputPage (pas de ResponsePage)");
 
     // 🎯 NOUVEAU : Envoi direct vers InputPage (pas besoin d'attendre response:ready)
-    println!("✅ Émission chat:start vers InputPage");
+    debug!("✅ Émission chat:start vers InputPage");
     let _ = app.emit_to(
         "input",
         "chat:start",
@@ -788,35 +1090,39 @@ fn start_chat(app: AppHandle, message: String) -> tauri::Result<()> {
     tauri::async_runtime::spawn(async move {
         match openai::chat_with_openai(openai::ChatRequest {
             message: message.clone(),
-            conversation_id: None,
-            context: None,
+            conversation_id,
+            context, // ex: texte OCR de la capture
         }).await {
             Ok(chat_response) => {
-                println!("🤖 OpenAI response: {}", chat_response.message);
-                println!("🚀 Emitting chat:response to input window");
+                // Requête acceptée : les pièces jointes envoyées ne sont plus en attente
+                app_clone.state::<attachments::AttachmentState>().remove_sent(&attached_ids);
+                debug!(body = %chat_response.message, "🤖 OpenAI response");
+                debug!("🚀 Emitting chat:response to input window");
                 let _ = app_clone.emit_to("input", "chat:response", serde_json::json!({
                     "message": chat_response.message,
                     "conversation_id": chat_response.conversation_id,
                     "tokens_used": chat_response.tokens_used,
                     "model": chat_response.model,
                 }));
-                println!("✅ chat:response emitted");
+                debug!("✅ chat:response emitted");
             }
             Err(e) => {
-                println!("❌ OpenAI error: {}", e);
+                error!("❌ OpenAI error: {}", e);
+                // kind : rate_limited, busy, timeout... pour adapter le message côté frontend
                 let _ = app_clone.emit_to("input", "chat:error", serde_json::json!({
                     "error": e.to_string(),
+                    "kind": e.kind(),
+                    "retry_after_ms": e.retry_after_ms(),
                 }));
             }
         }
     });
-
-    Ok(())
 }
 
 // === INPUT WINDOW MANAGEMENT ===
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
 fn input_show(app: AppHandle) -> tauri::Result<()> {
     // 🎛️ Nouvelle approche : Créer InputPage comme NSPanel indépendant
     #[cfg(all(target_os = "macos", feature = "stealth_macos"))]
@@ -828,23 +1134,7 @@ fn input_show(app: AppHandle) -> tauri::Result<()> {
         // Si la fenêtre n'existe pas, la créer
         if app.get_webview_window("input").is_none() {
             info!("🎛️ Création de la fenêtre Input...");
-            let config = DraggableWindowConfig::for_input();
-            let window = WebviewWindowBuilder::new(
-                &app,
-                "input",
-                WebviewUrl::External(format!("http://localhost:1420/{}", config.route).parse().unwrap()),
-            )
-            .decorations(false)
-            .transparent(true)
-            .always_on_top(true)
-            .resizable(true)
-            .minimizable(false)
-            .closable(false)
-            .skip_taskbar(true)
-            .inner_size(config.width, config.height)
-            .position(config.initial_x, config.initial_y)
-            .visible(false)
-            .build()?;
+            let window = draggable_window_builder(&app, "input", &DraggableWindowConfig::for_input()).build()?;
             
             info!("✅ Fenêtre Input créée - transformation en NSPanel...");
             // Transformer en NSPanel
@@ -869,6 +1159,7 @@ fn input_show(app: AppHandle) -> tauri::Result<()> {
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
 fn input_hide(app: AppHandle) -> tauri::Result<()> {
     #[cfg(all(target_os = "macos", feature = "stealth_macos"))]
     {
@@ -892,26 +1183,34 @@ fn input_hide(app: AppHandle) -> tauri::Result<()> {
 }
 
 #[tauri::command]
-fn input_resize(app: AppHandle, width: f64, height: f64) -> tauri::Result<()> {
-    if let Some(input) = app.get_webview_window("input") {
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+fn input_resize(app: AppHandle, width: f64, height: f64) -> Result<(), String> {
+    validation::validate_input("input_resize", validation::WindowSize { width, height }, |size| {
+        resize_docked_window(&app, "input", size).map_err(|e| e.to_string())
+    })
+}
+
+/// Redimensionne une fenêtre ancrée au HUD en gardant son bord haut fixe
+fn resize_docked_window(app: &AppHandle, label: &str, size: validation::WindowSize) -> tauri::Result<()> {
+    if let Some(window) = app.get_webview_window(label) {
         // 🎯 AGRANDISSEMENT VERS LE BAS : Garder la position Y fixe
-        let current_pos = input.outer_position()?;
-        let new_size = tauri::LogicalSize::new(width, height);
+        let current_pos = window.outer_position()?;
+        let new_size = tauri::LogicalSize::new(size.width, size.height);
 
         // Redimensionner la fenêtre
-        input.set_size(new_size)?;
+        window.set_size(new_size)?;
 
         // Repositionner pour garder le haut fixe (la fenêtre ne doit que s'étendre vers le bas)
-        input.set_position(current_pos)?;
+        window.set_position(current_pos)?;
 
         // Réorganiser le layout si la taille a changé
-        let _ = arrange_hud_children(&app);
+        let _ = arrange_hud_children(app);
 
         // Force redraw sur macOS pour éviter les artefacts visuels
         #[cfg(all(target_os = "macos", feature = "stealth_macos"))]
         unsafe {
             use objc::{msg_send, sel, sel_impl};
-            let win = input.ns_window()? as *mut objc::runtime::Object;
+            let win = window.ns_window()? as *mut objc::runtime::Object;
             let _: () = msg_send![win, display];
         }
     }
@@ -919,11 +1218,13 @@ fn input_resize(app: AppHandle, width: f64, height: f64) -> tauri::Result<()> {
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
 fn input_undock(app: AppHandle) -> tauri::Result<()> {
     undock_draggable_window(&app, "input")
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
 fn get_hud_position_and_size(app: AppHandle) -> tauri::Result<serde_json::Value> {
     if let Some(hud) = app.get_webview_window("hud") {
         let position = hud.outer_position()?;
@@ -941,6 +1242,7 @@ fn get_hud_position_and_size(app: AppHandle) -> tauri::Result<serde_json::Value>
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
 fn check_snap_distance(app: AppHandle) -> tauri::Result<serde_json::Value> {
     if let Some(hud) = app.get_webview_window("hud") {
         let hud_pos = hud.outer_position()?;
@@ -971,7 +1273,7 @@ fn check_snap_distance(app: AppHandle) -> tauri::Result<serde_json::Value> {
                     in_bottom >= hud_top &&
                     in_top <= hud_bottom;
 
-                println!("🎯 Input/HUD overlap: input=({}, {} -> {}, {}), hud=({}, {} -> {}, {}), should_snap={}",
+                debug!("🎯 Input/HUD overlap: input=({}, {} -> {}, {}), hud=({}, {} -> {}, {}), should_snap={}",
                     in_left, in_top, in_right, in_bottom, hud_left, hud_top, hud_right, hud_bottom, is_in_snap_zone);
 
                 return Ok(serde_json::json!({
@@ -1012,7 +1314,7 @@ fn check_snap_distance(app: AppHandle) -> tauri::Result<serde_json::Value> {
                     ctx_bottom >= hud_top &&
                     ctx_top <= hud_bottom;
 
-                println!("🎯 Context/HUD overlap: context=({}, {} -> {}, {}), hud=({}, {} -> {}, {}), should_snap={}",
+                debug!("🎯 Context/HUD overlap: context=({}, {} -> {}, {}), hud=({}, {} -> {}, {}), should_snap={}",
                     ctx_left, ctx_top, ctx_right, ctx_bottom, hud_left, hud_top, hud_right, hud_bottom, is_in_snap_zone);
 
                 return Ok(serde_json::json!({
@@ -1041,11 +1343,13 @@ fn check_snap_distance(app: AppHandle) -> tauri::Result<serde_json::Value> {
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
 fn input_dock(app: AppHandle) -> tauri::Result<()> {
     dock_draggable_window(&app, "input", DraggableWindowConfig::for_input())
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
 fn start_input_dragging(app: AppHandle) -> tauri::Result<()> {
     start_dragging_window(&app, "input")
 }
@@ -1054,6 +1358,7 @@ fn start_input_dragging(app: AppHandle) -> tauri::Result<()> {
 // ======================== CONTEXT PAGE COMMANDS =============================
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
 fn context_show(app: AppHandle) -> tauri::Result<()> {
     // 🎛️ Nouvelle approche : Créer ContextPage comme NSPanel indépendant
     #[cfg(all(target_os = "macos", feature = "stealth_macos"))]
@@ -1065,23 +1370,7 @@ fn context_show(app: AppHandle) -> tauri::Result<()> {
         // Si la fenêtre n'existe pas, la créer
         if app.get_webview_window("context").is_none() {
             info!("🎛️ Création de la fenêtre Context...");
-            let config = DraggableWindowConfig::for_context();
-            let window = WebviewWindowBuilder::new(
-                &app,
-                "context",
-                WebviewUrl::External(format!("http://localhost:1420/{}", config.route).parse().unwrap()),
-            )
-            .decorations(false)
-            .transparent(true)
-            .always_on_top(true)
-            .resizable(true)
-            .minimizable(false)
-            .closable(false)
-            .skip_taskbar(true)
-            .inner_size(config.width, config.height)
-            .position(config.initial_x, config.initial_y)
-            .visible(false)
-            .build()?;
+            let window = draggable_window_builder(&app, "context", &DraggableWindowConfig::for_context()).build()?;
             
             info!("✅ Fenêtre Context créée - transformation en NSPanel...");
             // Transformer en NSPanel
@@ -1106,6 +1395,7 @@ fn context_show(app: AppHandle) -> tauri::Result<()> {
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
 fn context_hide(app: AppHandle) -> tauri::Result<()> {
     #[cfg(all(target_os = "macos", feature = "stealth_macos"))]
     {
@@ -1123,56 +1413,40 @@ fn context_hide(app: AppHandle) -> tauri::Result<()> {
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
 fn context_undock(app: AppHandle) -> tauri::Result<()> {
     undock_draggable_window(&app, "context")
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
 fn context_dock(app: AppHandle) -> tauri::Result<()> {
     dock_draggable_window(&app, "context", DraggableWindowConfig::for_context())
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
 fn check_context_snap_distance(app: AppHandle) -> tauri::Result<serde_json::Value> {
     check_snap_distance_for_window(&app, "context")
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
 fn start_context_dragging(app: AppHandle) -> tauri::Result<()> {
     start_dragging_window(&app, "context")
 }
 
 #[tauri::command]
-fn context_resize(app: AppHandle, width: f64, height: f64) -> tauri::Result<()> {
-    if let Some(context) = app.get_webview_window("context") {
-        let current_pos = context.outer_position()?;
-        let new_size = tauri::LogicalSize::new(width, height);
-
-        // Redimensionner la fenêtre
-        context.set_size(new_size)?;
-        context.set_position(current_pos)?;
-
-        // Réorganiser le layout si la taille a changé
-        let _ = arrange_hud_children(&app);
-
-        // Force redraw sur macOS
-        #[cfg(all(target_os = "macos", feature = "stealth_macos"))]
-        unsafe {
-            use objc::{msg_send, sel, sel_impl};
-            let win = context.ns_window()? as *mut objc::runtime::Object;
-            let _: () = msg_send![win, display];
-        }
-    }
-    Ok(())
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+fn context_resize(app: AppHandle, width: f64, height: f64) -> Result<(), String> {
+    validation::validate_input("context_resize", validation::WindowSize { width, height }, |size| {
+        resize_docked_window(&app, "context", size).map_err(|e| e.to_string())
+    })
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
 fn toggle_stealth_cmd(app: AppHandle) -> Result<bool, String> {
-    use validation::check_rate_limit;
-
-    // Rate limit cette commande sensible
-    check_rate_limit("toggle_stealth_cmd").map_err(|e| e.to_string())?;
-
     stealth::toggle_stealth(&app).map_err(|e| e.to_string())?;
     let active = app.state::<stealth::StealthState>().is_active();
 
@@ -1190,25 +1464,28 @@ fn toggle_stealth_cmd(app: AppHandle) -> Result<bool, String> {
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all)]
 fn get_stealth_status(app: AppHandle) -> bool {
     app.state::<stealth::StealthState>().is_active()
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all)]
 fn test_stealth_manual(app: AppHandle) {
-    println!("🧪 Test manuel du mode furtif");
+    info!("🧪 Test manuel du mode furtif");
     let _ = stealth::toggle_stealth(&app);
 }
 
 // Commandes pour le stockage sécurisé - avec validation
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
 fn secure_store(key: String, value: String) -> Result<(), String> {
     use keyring::Entry;
-    use validation::{validate_and_rate_limit, SecureKeyValue};
+    use validation::{validate_input, SecureKeyValue};
 
     let kv = SecureKeyValue { key, value };
 
-    validate_and_rate_limit("secure_store", kv, |validated_kv| {
+    validate_input("secure_store", kv, |validated_kv| {
         let entry = Entry::new("numa", &validated_kv.key)
             .map_err(|e| format!("Erreur lors de la création de l'entrée: {}", e))?;
 
@@ -1220,13 +1497,14 @@ fn secure_store(key: String, value: String) -> Result<(), String> {
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
 fn secure_load(key: String) -> Result<String, String> {
     use keyring::Entry;
-    use validation::{validate_and_rate_limit, SecureKey};
+    use validation::{validate_input, SecureKey};
 
     let secure_key = SecureKey { key };
 
-    validate_and_rate_limit("secure_load", secure_key, |validated_key| {
+    validate_input("secure_load", secure_key, |validated_key| {
         let entry = Entry::new("numa", &validated_key.key)
             .map_err(|e| format!("Erreur lors de la création de l'entrée: {}", e))?;
 
@@ -1236,13 +1514,14 @@ fn secure_load(key: String) -> Result<String, String> {
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
 fn secure_delete(key: String) -> Result<(), String> {
     use keyring::Entry;
-    use validation::{validate_and_rate_limit, SecureKey};
+    use validation::{validate_input, SecureKey};
 
     let secure_key = SecureKey { key };
 
-    validate_and_rate_limit("secure_delete", secure_key, |validated_key| {
+    validate_input("secure_delete", secure_key, |validated_key| {
         let entry = Entry::new("numa", &validated_key.key)
             .map_err(|e| format!("Erreur lors de la création de l'entrée: {}", e))?;
 
@@ -1254,6 +1533,91 @@ fn secure_delete(key: String) -> Result<(), String> {
 }
 
 
+// 🚦 Toutes les commandes IPC : type d'entrée validé + classe de rate limit (voir ipc.rs)
+ipc::commands! {
+    capture_and_analyze: ipc::NoArgs => Capture,
+    capture_screen: ipc::NoArgs => Capture,
+    get_image_as_base64: ipc::ImagePathArgs => Query,
+    close_all_windows: ipc::NoArgs => Ui,
+    start_window_dragging: ipc::NoArgs => Ui,
+    force_hud_redraw: ipc::NoArgs => Ui,
+    resize_window: validation::WindowSize => Ui,
+    panel_show: ipc::NoArgs => Ui,
+    panel_hide: ipc::NoArgs => Ui,
+    input_show: ipc::NoArgs => Ui,
+    input_hide: ipc::NoArgs => Ui,
+    input_resize: validation::WindowSize => Ui,
+    input_undock: ipc::NoArgs => Ui,
+    input_dock: ipc::NoArgs => Ui,
+    get_hud_position_and_size: ipc::NoArgs => Query,
+    check_snap_distance: ipc::NoArgs => Ui,
+    start_input_dragging: ipc::NoArgs => Ui,
+    context_show: ipc::NoArgs => Ui,
+    context_hide: ipc::NoArgs => Ui,
+    context_undock: ipc::NoArgs => Ui,
+    context_dock: ipc::NoArgs => Ui,
+    check_context_snap_distance: ipc::NoArgs => Ui,
+    start_context_dragging: ipc::NoArgs => Ui,
+    context_resize: validation::WindowSize => Ui,
+    start_chat: ipc::StartChatArgs => Chat,
+    toggle_stealth_cmd: ipc::NoArgs => Settings,
+    get_stealth_status: ipc::NoArgs => Query,
+    test_stealth_manual: ipc::NoArgs => Settings,
+    secure_store: validation::SecureKeyValue => Secret,
+    secure_load: validation::SecureKey => Secret,
+    secure_delete: validation::SecureKey => Secret,
+    capture::get_capture_options: ipc::NoArgs => Query,
+    capture::set_capture_options: ipc::CaptureSettingsArgs => Settings,
+    ocr::ocr_capture: ipc::ImagePathArgs => Capture,
+    history::list_captures: ipc::ListCapturesArgs => Query,
+    history::search_captures: ipc::SearchCapturesArgs => Query,
+    history::get_capture_thumbnail: ipc::CaptureThumbnailArgs => Query,
+    history::delete_capture: ipc::CaptureIdArgs => Settings,
+    attach_capture_to_chat: ipc::AttachCaptureArgs => Chat,
+    window_capture::list_windows: ipc::NoArgs => Query,
+    window_capture::capture_window: ipc::CaptureWindowArgs => Capture,
+    clipboard::get_clipboard_permission: ipc::NoArgs => Query,
+    clipboard::set_clipboard_permission: ipc::ClipboardPermissionArgs => Settings,
+    clipboard::read_clipboard_context: ipc::NoArgs => Capture,
+    clipboard::copy_answer: ipc::CopyAnswerArgs => Capture,
+    clipboard::copy_code_block: ipc::CopyCodeBlockArgs => Capture,
+    clipboard::get_clipboard_audit: ipc::NoArgs => Query,
+    attachments::add_attachments: ipc::AddAttachmentsArgs => Capture,
+    attachments::list_attachments: ipc::NoArgs => Query,
+    attachments::remove_attachment: ipc::AttachmentIdArgs => Settings,
+    rate_limit::get_rate_limit_quota: ipc::QuotaArgs => Query,
+    audit::get_audit_log: ipc::AuditLogArgs => Query,
+    crash::list_crash_reports: ipc::NoArgs => Query,
+    crash::export_crash_report: ipc::CrashIdArgs => Query,
+    crash_upload::get_crash_reporting_status: ipc::NoArgs => Query,
+    metrics::get_backend_metrics: ipc::NoArgs => Query,
+    consent::get_consent: ipc::NoArgs => Query,
+    consent::set_consent: ipc::ConsentArgs => Settings,
+    consent::revoke_consent: ipc::NoArgs => Settings,
+    logging::get_log_levels: ipc::NoArgs => Query,
+    logging::set_log_level: ipc::LogLevelArgs => Settings,
+    log_tail::subscribe_logs: ipc::LogTailArgs => Query,
+    log_tail::unsubscribe_logs: ipc::NoArgs => Query,
+    diagnostics::export_diagnostics: ipc::NoArgs => Capture,
+    redaction::get_redaction_settings: ipc::NoArgs => Query,
+    redaction::set_redaction_settings: ipc::RedactionSettingsArgs => Settings,
+    redaction::redact_capture: ipc::RedactCaptureArgs => Capture,
+    csp_manager::get_dynamic_csp_policy: ipc::NoArgs => Query,
+    csp_manager::get_csp_for_context: ipc::CspContextArgs => Query,
+    openai::chat_with_openai: ipc::ChatWithOpenAiArgs => SelfLimited,
+    openai::store_openai_key: ipc::OpenAiKeyArgs => Secret,
+    openai::get_chat_config: ipc::NoArgs => Query,
+    ns_panel::init_ns_panel: ipc::ShortcutArgs => Ui,
+    ns_panel::init_context_ns_panel: ipc::NoArgs => Ui,
+    ns_panel::init_input_ns_panel: ipc::NoArgs => Ui,
+    ns_panel::show_app: ipc::NoArgs => Ui,
+    ns_panel::hide_app: ipc::NoArgs => Ui,
+    ns_panel::show_context_panel: ipc::NoArgs => Ui,
+    ns_panel::hide_context_panel: ipc::NoArgs => Ui,
+    ns_panel::show_input_panel: ipc::NoArgs => Ui,
+    ns_panel::hide_input_panel: ipc::NoArgs => Ui,
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // 🚀 Initialize comprehensive logging system FIRST
@@ -1271,63 +1635,53 @@ pub fn run() {
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_dialog::init())
         .manage(stealth::StealthState::default())
         .manage(ns_panel::State::default())
-        .invoke_handler(tauri::generate_handler![
-            capture_and_analyze,
-            capture_screen,
-            get_image_as_base64,
-            close_all_windows,
-            start_window_dragging,
-            force_hud_redraw,
-            resize_window,
-            panel_show,
-            panel_hide,
-            input_show,
-            input_hide,
-            input_resize,
-            input_undock,
-            input_dock,
-            get_hud_position_and_size,
-            check_snap_distance,
-            start_input_dragging,
-            context_show,
-            context_hide,
-            context_undock,
-            context_dock,
-            check_context_snap_distance,
-            start_context_dragging,
-            context_resize,
-            start_chat,
-            toggle_stealth_cmd,
-            get_stealth_status,
-            test_stealth_manual,
-            secure_store,
-            secure_load,
-            secure_delete,
-            csp_manager::get_dynamic_csp_policy,
-            csp_manager::get_csp_for_context,
-            openai::chat_with_openai,
-            openai::store_openai_key,
-            openai::get_chat_config,
-            ns_panel::init_ns_panel,
-            ns_panel::init_context_ns_panel,
-            ns_panel::init_input_ns_panel,
-            ns_panel::show_app,
-            ns_panel::hide_app,
-            ns_panel::show_context_panel,
-            ns_panel::hide_context_panel,
-            ns_panel::show_input_panel,
-            ns_panel::hide_input_panel
-        ])
+        .manage(ocr::OcrState::default())
+        .manage(history::HistoryState::default())
+        .manage(attachments::AttachmentState::default())
+        .invoke_handler(invoke_handler())
         .on_window_event(|window, event| {
-            if let tauri::WindowEvent::CloseRequested { .. } = event {
-                window.app_handle().exit(0);
+            match event {
+                tauri::WindowEvent::CloseRequested { .. } => {
+                    window.app_handle().exit(0);
+                }
+                // 📎 Fichiers déposés sur l'InputPage
+                tauri::WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) if window.label() == "input" => {
+                    attachments::handle_drop(window.app_handle(), paths.clone());
+                }
+                _ => {}
             }
         })
         .setup(|app| {
             info!("Setting up application...");
 
+            // ⏱️ Limites de débit personnalisées (app config dir)
+            if let Ok(config_dir) = app.path().app_config_dir() {
+                rate_limit::load_overrides(&config_dir);
+            }
+
+            // 🧾 Journal d'audit des commandes sensibles, 💥 rapports de crash des lancements précédents
+            if let Ok(data_dir) = app.path().app_data_dir() {
+                audit::init(&data_dir.join("audit"));
+                crash::init(&data_dir.join("crashes"));
+            }
+
+            // ✅ Consentement télémétrie, puis 📮 envoi des rapports de crash en attente (endpoint configuré + consentement)
+            if let Ok(config_dir) = app.path().app_config_dir() {
+                consent::init(&config_dir);
+                crash_upload::start(app.handle(), &config_dir);
+            }
+
+            // 📜 Suivi des logs en direct pour la fenêtre de debug
+            log_tail::start(app.handle().clone());
+
+            // 🛡️ HUD construit ici (create: false dans tauri.conf.json) pour recevoir la CSP calculée après le consentement
+            if let Some(builder) = hud_builder(app.handle())? {
+                builder.build()?;
+            }
+
             // Forcer le HUD au premier plan
             if let Some(hud_win) = app.get_webview_window("hud") {
                 hud_win.set_focus().ok();
@@ -0,0 +1,271 @@
+// src-tauri/src/log_tail.rs
+//! 📜 Live log tail
+//!
+//! Features:
+//! - Last `TAIL_RECORDS` log records kept in memory: level, target, redacted message
+//! - `subscribe_logs` returns the matching backlog, new records follow as `log:record` events
+//! - Level and target filters per subscribed window
+//! - Events are emitted by a forwarder thread, never from inside the tracing callback
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::{HashMap, VecDeque};
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, WebviewWindow};
+use tracing::{info, Event, Level, Subscriber};
+use tracing_subscriber::fmt::format::{FormatFields, Writer};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::Layer;
+use crate::validation::ValidatedInput;
+
+/// Records kept for new subscribers
+pub const TAIL_RECORDS: usize = 1000;
+
+/// Event carrying one `LogRecord` to a subscribed window
+pub const RECORD_EVENT: &str = "log:record";
+
+/// Records waiting for the forwarder, newer ones are dropped when it lags
+const FORWARD_QUEUE: usize = 1024;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<Level> for LogLevel {
+    fn from(level: Level) -> Self {
+        match level {
+            Level::ERROR => LogLevel::Error,
+            Level::WARN => LogLevel::Warn,
+            Level::INFO => LogLevel::Info,
+            Level::DEBUG => LogLevel::Debug,
+            Level::TRACE => LogLevel::Trace,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct LogRecord {
+    /// Increasing, to merge the backlog with the events
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    pub level: LogLevel,
+    pub target: String,
+    /// Formatted fields, redacted like the log files
+    pub message: String,
+}
+
+/// Filters of `subscribe_logs`, all optional
+#[derive(Deserialize, Debug, Clone, Default, ValidatedInput)]
+pub struct LogTailFilter {
+    /// Most verbose level shown: `info` shows info, warn and error
+    pub level: Option<LogLevel>,
+    /// Target prefix, e.g. `tauri_app_lib::openai`
+    #[validate(non_empty, len(max = 128), charset = "alnum_:")]
+    pub target: Option<String>,
+}
+
+impl LogTailFilter {
+    pub fn matches(&self, record: &LogRecord) -> bool {
+        self.level.is_none_or(|level| record.level <= level)
+            && self.target.as_ref().is_none_or(|target| record.target.starts_with(target.as_str()))
+    }
+}
+
+struct Tail {
+    records: VecDeque<LogRecord>,
+    last_seq: u64,
+}
+
+static TAIL: Lazy<Mutex<Tail>> =
+    Lazy::new(|| Mutex::new(Tail { records: VecDeque::with_capacity(TAIL_RECORDS), last_seq: 0 }));
+
+struct Subscription {
+    filter: LogTailFilter,
+    /// Last record already returned in the backlog
+    after: u64,
+}
+
+/// Subscribed windows by label
+static SUBSCRIPTIONS: Lazy<Mutex<HashMap<String, Subscription>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Set by `start` once the app handle exists
+static FORWARD: OnceCell<SyncSender<LogRecord>> = OnceCell::new();
+
+thread_local! {
+    /// Set on the forwarder thread: its own logs would feed the tail they are emitted from
+    static FORWARDING: Cell<bool> = const { Cell::new(false) };
+}
+
+fn push(level: LogLevel, target: &str, message: String) -> LogRecord {
+    // Still usable after a panic in another logging thread
+    let mut tail = TAIL.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    tail.last_seq += 1;
+    let record = LogRecord { seq: tail.last_seq, timestamp: Utc::now(), level, target: target.to_string(), message };
+
+    if tail.records.len() == TAIL_RECORDS {
+        tail.records.pop_front();
+    }
+    tail.records.push_back(record.clone());
+    record
+}
+
+/// Layer feeding the tail, `fields` is the redacting formatter of the other outputs
+pub struct TailLayer<F> {
+    fields: F,
+}
+
+pub fn layer<F>(fields: F) -> TailLayer<F> {
+    TailLayer { fields }
+}
+
+impl<S, F> Layer<S> for TailLayer<F>
+where
+    S: Subscriber,
+    F: for<'writer> FormatFields<'writer> + 'static,
+{
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if FORWARDING.with(Cell::get) {
+            return;
+        }
+
+        let mut message = String::new();
+        if self.fields.format_fields(Writer::new(&mut message), event).is_err() {
+            return;
+        }
+
+        let meta = event.metadata();
+        let record = push(LogLevel::from(*meta.level()), meta.target(), message);
+        if let Some(forward) = FORWARD.get() {
+            let _ = forward.try_send(record);
+        }
+    }
+}
+
+/// Matching records, oldest first, and the last sequence number they cover
+fn backlog(tail: &Tail, filter: &LogTailFilter) -> (Vec<LogRecord>, u64) {
+    let records = tail.records.iter().filter(|record| filter.matches(record)).cloned().collect();
+    (records, tail.last_seq)
+}
+
+fn subscribe(label: &str, filter: LogTailFilter) -> Vec<LogRecord> {
+    // Tail locked until the subscription exists: no record between backlog and events is lost
+    let tail = TAIL.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let (records, after) = backlog(&tail, &filter);
+    SUBSCRIPTIONS.lock().unwrap().insert(label.to_string(), Subscription { filter, after });
+    records
+}
+
+fn unsubscribe(label: &str) -> bool {
+    SUBSCRIPTIONS.lock().unwrap().remove(label).is_some()
+}
+
+/// Subscribed windows a record goes to
+fn recipients(record: &LogRecord) -> Vec<String> {
+    SUBSCRIPTIONS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(_, sub)| record.seq > sub.after && sub.filter.matches(record))
+        .map(|(label, _)| label.clone())
+        .collect()
+}
+
+/// Start the forwarder thread, called once at startup
+pub fn start(app: AppHandle) {
+    let (sender, receiver) = mpsc::sync_channel::<LogRecord>(FORWARD_QUEUE);
+    if FORWARD.set(sender).is_err() {
+        return;
+    }
+
+    let spawned = std::thread::Builder::new().name("log-tail".into()).spawn(move || {
+        FORWARDING.with(|forwarding| forwarding.set(true));
+        for record in receiver {
+            for label in recipients(&record) {
+                if app.get_webview_window(&label).is_none() {
+                    // Closed without unsubscribing
+                    unsubscribe(&label);
+                    continue;
+                }
+                let _ = app.emit_to(label.as_str(), RECORD_EVENT, &record);
+            }
+        }
+    });
+    if let Err(e) = spawned {
+        tracing::warn!("📜 Live log tail unavailable: {}", e);
+    }
+}
+
+/// Records already kept, newest last; the following ones arrive as `log:record` events
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all)]
+pub fn subscribe_logs(window: WebviewWindow, filter: Option<LogTailFilter>) -> Vec<LogRecord> {
+    info!("📜 Window '{}' follows the logs", window.label());
+    subscribe(window.label(), filter.unwrap_or_default())
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all)]
+pub fn unsubscribe_logs(window: WebviewWindow) -> bool {
+    unsubscribe(window.label())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(level: LogLevel, target: &str) -> LogRecord {
+        LogRecord { seq: 1, timestamp: Utc::now(), level, target: target.to_string(), message: String::new() }
+    }
+
+    #[test]
+    fn test_filter_matching() {
+        let warn = LogTailFilter { level: Some(LogLevel::Warn), target: None };
+        assert!(warn.matches(&record(LogLevel::Error, "tauri_app_lib")));
+        assert!(warn.matches(&record(LogLevel::Warn, "tauri_app_lib")));
+        assert!(!warn.matches(&record(LogLevel::Info, "tauri_app_lib")));
+
+        let openai = LogTailFilter { level: None, target: Some("tauri_app_lib::openai".into()) };
+        assert!(openai.matches(&record(LogLevel::Trace, "tauri_app_lib::openai")));
+        assert!(!openai.matches(&record(LogLevel::Error, "tauri_app_lib::ocr")));
+        assert!(LogTailFilter::default().matches(&record(LogLevel::Trace, "hyper")));
+
+        let invalid = LogTailFilter { level: None, target: Some("x\"><script>".into()) };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_tail_is_bounded_and_subscriptions_start_after_the_backlog() {
+        let marker = uuid::Uuid::new_v4().to_string();
+        let target = format!("tail_test_{}", marker.replace('-', "_"));
+        for i in 0..TAIL_RECORDS + 5 {
+            push(LogLevel::Info, &target, format!("line {}", i));
+        }
+
+        let label = format!("tail-test-{}", marker);
+        let filter = LogTailFilter { level: Some(LogLevel::Info), target: Some(target.clone()) };
+        let backlog = subscribe(&label, filter);
+        assert!(backlog.len() <= TAIL_RECORDS);
+        assert_eq!(backlog.last().unwrap().message, format!("line {}", TAIL_RECORDS + 4));
+        assert!(backlog.iter().all(|record| record.message != "line 0"));
+        assert!(backlog.windows(2).all(|pair| pair[0].seq < pair[1].seq));
+
+        // Already returned in the backlog, not sent again
+        assert!(!recipients(backlog.last().unwrap()).contains(&label));
+        let newer = push(LogLevel::Info, &target, "new".into());
+        assert!(recipients(&newer).contains(&label));
+        let verbose = push(LogLevel::Debug, &target, "too verbose".into());
+        assert!(!recipients(&verbose).contains(&label));
+
+        assert!(unsubscribe(&label));
+        assert!(!recipients(&push(LogLevel::Info, &target, "after".into())).contains(&label));
+    }
+}
@@ -0,0 +1,401 @@
+// src-tauri/src/metrics.rs
+//! 📊 Backend metrics
+//!
+//! Features:
+//! - Tracing layer timing the spans of IPC commands (`target = "ipc"`) and of the OpenAI call
+//! - Latency, outcome and payload sizes per command, p50/p95/p99 over the last `WINDOW` calls
+//! - Rejections by the IPC middleware counted apart from command errors
+//! - `get_backend_metrics` command polled by the metrics dashboard
+//! - Names and sizes only: arguments and results never reach this module
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::io;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::filter::{filter_fn, Filtered};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// Span targets turned into metrics
+pub const IPC_TARGET: &str = "ipc";
+pub const OPENAI_TARGET: &str = "openai";
+
+/// Samples kept per command for the percentiles
+pub const WINDOW: usize = 1024;
+
+/// Span fields read by the layer
+const REQUEST_BYTES: &str = "request_bytes";
+const RESPONSE_BYTES: &str = "response_bytes";
+const OUTCOME: &str = "outcome";
+/// Field of the event emitted by `#[instrument(err)]`
+const ERROR: &str = "error";
+
+fn is_metric_target(target: &str) -> bool {
+    target == IPC_TARGET || target == OPENAI_TARGET
+}
+
+/// Last `WINDOW` values of one measure
+#[derive(Debug, Default)]
+struct Samples(VecDeque<u64>);
+
+impl Samples {
+    fn push(&mut self, value: u64) {
+        if self.0.len() == WINDOW {
+            self.0.pop_front();
+        }
+        self.0.push_back(value);
+    }
+
+    fn sorted(&self) -> Vec<u64> {
+        let mut values: Vec<u64> = self.0.iter().copied().collect();
+        values.sort_unstable();
+        values
+    }
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[u64], p: f64) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[derive(Debug, Default)]
+struct CommandStats {
+    calls: u64,
+    errors: u64,
+    rejected: u64,
+    latency_us: Samples,
+    request_bytes: Samples,
+    response_bytes: Samples,
+}
+
+struct Metrics {
+    started: Instant,
+    commands: BTreeMap<String, CommandStats>,
+}
+
+static METRICS: Lazy<Mutex<Metrics>> =
+    Lazy::new(|| Mutex::new(Metrics { started: Instant::now(), commands: BTreeMap::new() }));
+
+fn with_stats(name: &str, update: impl FnOnce(&mut CommandStats)) {
+    // Metrics are best effort, a poisoned lock is still usable
+    let mut metrics = METRICS.lock().unwrap_or_else(|e| e.into_inner());
+    update(metrics.commands.entry(name.to_string()).or_default());
+}
+
+/// One finished command or call
+pub fn record_call(name: &str, latency: Duration, ok: bool, response_bytes: Option<u64>) {
+    with_stats(name, |stats| {
+        stats.calls += 1;
+        if !ok {
+            stats.errors += 1;
+        }
+        stats.latency_us.push(latency.as_micros().min(u64::MAX as u128) as u64);
+        if let Some(bytes) = response_bytes {
+            stats.response_bytes.push(bytes);
+        }
+    });
+}
+
+/// Arguments size of a command allowed by the middleware
+pub fn record_request(name: &str, bytes: u64) {
+    with_stats(name, |stats| stats.request_bytes.push(bytes));
+}
+
+/// Command refused by the middleware before running
+pub fn record_rejection(name: &str) {
+    with_stats(name, |stats| stats.rejected += 1);
+}
+
+/// Serialized size of `value`, without building the string
+pub fn json_len(value: &impl Serialize) -> u64 {
+    struct Counter(u64);
+
+    impl io::Write for Counter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0 += buf.len() as u64;
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut counter = Counter(0);
+    let _ = serde_json::to_writer(&mut counter, value);
+    counter.0
+}
+
+/// Timing of one instrumented span, kept in its extensions
+#[derive(Debug)]
+struct SpanTiming {
+    start: Instant,
+    request_bytes: Option<u64>,
+    response_bytes: Option<u64>,
+    failed: bool,
+}
+
+impl Visit for SpanTiming {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        match field.name() {
+            REQUEST_BYTES => self.request_bytes = Some(value),
+            RESPONSE_BYTES => self.response_bytes = Some(value),
+            _ => {}
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        if let Ok(value) = u64::try_from(value) {
+            self.record_u64(field, value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == OUTCOME {
+            self.failed = value != "ok";
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn fmt::Debug) {}
+}
+
+/// Whether an event carries an `error` field
+struct HasError(bool);
+
+impl Visit for HasError {
+    fn record_debug(&mut self, field: &Field, _value: &dyn fmt::Debug) {
+        self.0 |= field.name() == ERROR;
+    }
+}
+
+/// Error of a command, emitted in its span by `#[instrument(err)]`
+pub(crate) fn is_error_event(event: &Event<'_>) -> bool {
+    let mut has_error = HasError(false);
+    event.record(&mut has_error);
+    has_error.0
+}
+
+/// Name under which a span is aggregated: the command for IPC spans
+fn metric_name(target: &str, name: &str) -> String {
+    if target == IPC_TARGET {
+        name.to_string()
+    } else {
+        format!("{}::{}", target, name)
+    }
+}
+
+pub struct MetricsLayer;
+
+impl<S> Layer<S> for MetricsLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut timing = SpanTiming { start: Instant::now(), request_bytes: None, response_bytes: None, failed: false };
+        attrs.record(&mut timing);
+        span.extensions_mut().insert(timing);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut extensions = span.extensions_mut();
+        if let Some(timing) = extensions.get_mut::<SpanTiming>() {
+            values.record(timing);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if !is_error_event(event) {
+            return;
+        }
+        let Some(span) = ctx.event_span(event) else { return };
+        let mut extensions = span.extensions_mut();
+        if let Some(timing) = extensions.get_mut::<SpanTiming>() {
+            timing.failed = true;
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else { return };
+        let extensions = span.extensions();
+        let Some(timing) = extensions.get::<SpanTiming>() else { return };
+
+        let name = metric_name(span.metadata().target(), span.name());
+        record_call(&name, timing.start.elapsed(), !timing.failed, timing.response_bytes);
+        if let Some(bytes) = timing.request_bytes {
+            record_request(&name, bytes);
+        }
+    }
+}
+
+/// Metrics layer, only sees the spans and events of the metric targets
+pub fn layer<S>() -> Filtered<MetricsLayer, impl tracing_subscriber::layer::Filter<S>, S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    MetricsLayer.with_filter(filter_fn(|meta| is_metric_target(meta.target())))
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct Percentiles {
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct Sizes {
+    pub avg: u64,
+    pub max: u64,
+}
+
+impl Sizes {
+    fn of(samples: &Samples) -> Option<Self> {
+        let max = samples.0.iter().copied().max()?;
+        let avg = samples.0.iter().sum::<u64>() / samples.0.len() as u64;
+        Some(Self { avg, max })
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandMetrics {
+    pub command: String,
+    pub calls: u64,
+    pub errors: u64,
+    pub rejected: u64,
+    pub latency_ms: Percentiles,
+    pub request_bytes: Option<Sizes>,
+    pub response_bytes: Option<Sizes>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BackendMetrics {
+    pub uptime_secs: u64,
+    /// Calls per command the percentiles are computed on
+    pub window: usize,
+    pub commands: Vec<CommandMetrics>,
+}
+
+fn snapshot() -> BackendMetrics {
+    let metrics = METRICS.lock().unwrap_or_else(|e| e.into_inner());
+    let commands = metrics
+        .commands
+        .iter()
+        .map(|(command, stats)| {
+            let latency = stats.latency_us.sorted();
+            let ms = |us: u64| us as f64 / 1000.0;
+            CommandMetrics {
+                command: command.clone(),
+                calls: stats.calls,
+                errors: stats.errors,
+                rejected: stats.rejected,
+                latency_ms: Percentiles {
+                    p50: ms(percentile(&latency, 50.0)),
+                    p95: ms(percentile(&latency, 95.0)),
+                    p99: ms(percentile(&latency, 99.0)),
+                    max: ms(latency.last().copied().unwrap_or(0)),
+                },
+                request_bytes: Sizes::of(&stats.request_bytes),
+                response_bytes: Sizes::of(&stats.response_bytes),
+            }
+        })
+        .collect();
+
+    BackendMetrics { uptime_secs: metrics.started.elapsed().as_secs(), window: WINDOW, commands }
+}
+
+/// Latency, outcome and payload sizes per command since startup
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all)]
+pub fn get_backend_metrics() -> BackendMetrics {
+    snapshot()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing::Instrument;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    fn command(name: &str) -> Option<CommandMetrics> {
+        snapshot().commands.into_iter().find(|c| c.command == name)
+    }
+
+    #[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+    fn metrics_test_command(fail: bool) -> Result<(), String> {
+        std::thread::sleep(Duration::from_millis(2));
+        if fail { Err("nope".to_string()) } else { Ok(()) }
+    }
+
+    #[tracing::instrument(target = "ipc", skip_all)]
+    async fn metrics_test_async_command() {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let sorted: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&sorted, 50.0), 50);
+        assert_eq!(percentile(&sorted, 95.0), 95);
+        assert_eq!(percentile(&sorted, 99.0), 99);
+        assert_eq!(percentile(&[7], 99.0), 7);
+        assert_eq!(percentile(&[], 50.0), 0);
+
+        let mut samples = Samples::default();
+        (0..WINDOW as u64 + 10).for_each(|v| samples.push(v));
+        assert_eq!(samples.0.len(), WINDOW);
+        assert_eq!(samples.0.front(), Some(&10));
+    }
+
+    #[tokio::test]
+    async fn test_spans_become_command_metrics() {
+        let subscriber = tracing_subscriber::registry().with(layer());
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        metrics_test_command(false).unwrap();
+        metrics_test_command(true).unwrap_err();
+        record_request("metrics_test_command", 40);
+        record_rejection("metrics_test_command");
+        metrics_test_async_command().await;
+
+        let span = tracing::info_span!(target: "openai", "metrics_test_call", request_bytes = 1200u64, response_bytes = tracing::field::Empty);
+        async { tracing::Span::current().record(RESPONSE_BYTES, 300u64); }.instrument(span).await;
+
+        let sync = command("metrics_test_command").unwrap();
+        assert_eq!((sync.calls, sync.errors, sync.rejected), (2, 1, 1));
+        assert!(sync.latency_ms.p50 >= 2.0, "{:?}", sync.latency_ms);
+        assert_eq!(sync.request_bytes, Some(Sizes { avg: 40, max: 40 }));
+
+        // Async spans stay open across awaits
+        let async_command = command("metrics_test_async_command").unwrap();
+        assert!(async_command.latency_ms.p99 >= 20.0, "{:?}", async_command.latency_ms);
+
+        let openai = command("openai::metrics_test_call").unwrap();
+        assert_eq!(openai.request_bytes, Some(Sizes { avg: 1200, max: 1200 }));
+        assert_eq!(openai.response_bytes, Some(Sizes { avg: 300, max: 300 }));
+
+        // Spans of other targets are ignored
+        tracing::info_span!("metrics_test_other").in_scope(|| {});
+        assert!(command("metrics_test_other").is_none());
+    }
+
+    #[test]
+    fn test_json_len() {
+        assert_eq!(json_len(&serde_json::json!({"a": "bc"})), r#"{"a":"bc"}"#.len() as u64);
+    }
+}
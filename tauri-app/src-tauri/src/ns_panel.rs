@@ -126,6 +126,7 @@ static INIT: Once = Once::new();
 static PANEL_LABEL: &str = "hud";
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all)]
 pub fn init_ns_panel(app_handle: AppHandle<Wry>, window: WebviewWindow<Wry>, shortcut: &str) {
     INIT.call_once(|| {
         set_state!(app_handle, panel, Some(create_ns_panel(&window)));
@@ -133,12 +134,19 @@ pub fn init_ns_panel(app_handle: AppHandle<Wry>, window: WebviewWindow<Wry>, sho
     });
 }
 
+/// Remplace le NSPanel d'un HUD reconstruit (le raccourci reste enregistré)
+pub fn replace_ns_panel(app_handle: &AppHandle<Wry>, window: &WebviewWindow<Wry>) {
+    set_state!(app_handle, panel, Some(create_ns_panel(window)));
+}
+
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all)]
 pub fn init_context_ns_panel(app_handle: AppHandle<Wry>, window: WebviewWindow<Wry>) {
     set_state!(app_handle, context_panel, Some(create_context_ns_panel(&window)));
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all)]
 pub fn init_input_ns_panel(app_handle: AppHandle<Wry>, window: WebviewWindow<Wry>) {
     set_state!(app_handle, input_panel, Some(create_input_ns_panel(&window)));
 }
@@ -150,16 +158,19 @@ fn register_shortcut(app_handle: AppHandle<Wry>, shortcut: &str) {
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all)]
 pub fn show_app(app_handle: AppHandle<Wry>) {
     panel!(app_handle).show();
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all)]
 pub fn hide_app(app_handle: AppHandle<Wry>) {
     panel!(app_handle).order_out(None);
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all)]
 pub fn show_context_panel(app_handle: AppHandle<Wry>) {
     let handle = app_handle.app_handle();
     let state = handle.state::<State>();
@@ -174,6 +185,7 @@ pub fn show_context_panel(app_handle: AppHandle<Wry>) {
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all)]
 pub fn hide_context_panel(app_handle: AppHandle<Wry>) {
     let handle = app_handle.app_handle();
     let state = handle.state::<State>();
@@ -187,6 +199,7 @@ pub fn hide_context_panel(app_handle: AppHandle<Wry>) {
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all)]
 pub fn show_input_panel(app_handle: AppHandle<Wry>) {
     let handle = app_handle.app_handle();
     let state = handle.state::<State>();
@@ -201,6 +214,7 @@ pub fn show_input_panel(app_handle: AppHandle<Wry>) {
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all)]
 pub fn hide_input_panel(app_handle: AppHandle<Wry>) {
     let handle = app_handle.app_handle();
     let state = handle.state::<State>();
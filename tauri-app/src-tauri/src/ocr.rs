@@ -0,0 +1,464 @@
+// src-tauri/src/ocr.rs
+//! 🔎 On-device OCR for captures
+//!
+//! Features:
+//! - Local text recognition with bundled `ocrs` models (no network)
+//! - Line and word bounding boxes in capture pixel coordinates
+//! - Plain-text context for text-only chat models
+
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+use thiserror::Error;
+use tracing::{info, warn, debug};
+
+/// Model file names expected in the OCR models directory
+const DETECTION_MODEL: &str = "text-detection.rten";
+const RECOGNITION_MODEL: &str = "text-recognition.rten";
+
+/// Environment override for the models directory
+const MODELS_DIR_ENV: &str = "NUMA_OCR_MODELS";
+
+/// Long edge used for recognition, larger captures are downscaled first
+const MAX_OCR_DIMENSION: u32 = 2560;
+
+/// Default size of the text handed to the chat as context
+pub const DEFAULT_CONTEXT_CHARS: usize = 4000;
+
+#[derive(Error, Debug)]
+pub enum OcrError {
+    #[error("OCR models not found (expected {DETECTION_MODEL} and {RECOGNITION_MODEL} in {0})")]
+    ModelsNotFound(String),
+
+    #[error("Failed to load OCR engine: {0}")]
+    EngineLoad(String),
+
+    #[error("Failed to read image: {0}")]
+    Image(String),
+
+    #[error("Text recognition failed: {0}")]
+    Recognition(String),
+}
+
+/// Axis-aligned box in image pixels
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl BoundingBox {
+    /// Build a box from signed edges, clamped to the image origin
+    pub fn from_edges(left: i32, top: i32, right: i32, bottom: i32) -> Self {
+        let (left, top) = (left.max(0), top.max(0));
+        Self {
+            x: left as u32,
+            y: top as u32,
+            width: (right - left).max(0) as u32,
+            height: (bottom - top).max(0) as u32,
+        }
+    }
+
+    /// Scale a box computed on a resized image back to the original size
+    pub fn scaled(&self, factor: f64) -> Self {
+        Self {
+            x: (self.x as f64 * factor).round() as u32,
+            y: (self.y as f64 * factor).round() as u32,
+            width: (self.width as f64 * factor).round() as u32,
+            height: (self.height as f64 * factor).round() as u32,
+        }
+    }
+}
+
+/// A recognized word
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OcrWord {
+    pub text: String,
+    pub bbox: BoundingBox,
+}
+
+/// A recognized line, in reading order
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OcrLine {
+    pub text: String,
+    pub bbox: BoundingBox,
+    pub words: Vec<OcrWord>,
+}
+
+/// OCR output for one image
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OcrResult {
+    pub width: u32,
+    pub height: u32,
+    pub lines: Vec<OcrLine>,
+}
+
+impl OcrResult {
+    /// Full text, one line per recognized line
+    pub fn text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| line.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Text suitable for `ChatRequest.context`, truncated on a line boundary
+    pub fn to_context(&self, max_chars: usize) -> Option<String> {
+        text_to_context(self.lines.iter().map(|line| line.text.as_str()), max_chars)
+    }
+}
+
+/// Build a chat context from OCR lines, truncated on a line boundary
+pub fn text_to_context<'a>(lines: impl IntoIterator<Item = &'a str>, max_chars: usize) -> Option<String> {
+    let mut context = String::new();
+
+    for line in lines {
+        let text = line.trim();
+        if text.is_empty() {
+            continue;
+        }
+        if context.chars().count() + text.chars().count() + 1 > max_chars {
+            break;
+        }
+        if !context.is_empty() {
+            context.push('\n');
+        }
+        context.push_str(text);
+    }
+
+    if context.is_empty() {
+        None
+    } else {
+        Some(format!("Text visible on the user's screen:\n{}", context))
+    }
+}
+
+/// Text recognition backend
+pub trait TextRecognizer: Send + Sync {
+    /// Recognize lines of text, boxes are relative to `image`
+    fn recognize(&self, image: &RgbaImage) -> Result<Vec<OcrLine>, OcrError>;
+}
+
+/// `ocrs` engine running the bundled detection and recognition models
+pub struct OcrsRecognizer {
+    engine: ocrs::OcrEngine,
+}
+
+impl OcrsRecognizer {
+    pub fn load(models_dir: &Path) -> Result<Self, OcrError> {
+        let detection_path = models_dir.join(DETECTION_MODEL);
+        let recognition_path = models_dir.join(RECOGNITION_MODEL);
+
+        if !detection_path.exists() || !recognition_path.exists() {
+            return Err(OcrError::ModelsNotFound(models_dir.display().to_string()));
+        }
+
+        let detection_model = rten::Model::load_file(&detection_path)
+            .map_err(|e| OcrError::EngineLoad(e.to_string()))?;
+        let recognition_model = rten::Model::load_file(&recognition_path)
+            .map_err(|e| OcrError::EngineLoad(e.to_string()))?;
+
+        let engine = ocrs::OcrEngine::new(ocrs::OcrEngineParams {
+            detection_model: Some(detection_model),
+            recognition_model: Some(recognition_model),
+            ..Default::default()
+        })
+        .map_err(|e| OcrError::EngineLoad(e.to_string()))?;
+
+        info!("🔎 OCR engine loaded from {}", models_dir.display());
+        Ok(Self { engine })
+    }
+}
+
+impl TextRecognizer for OcrsRecognizer {
+    fn recognize(&self, image: &RgbaImage) -> Result<Vec<OcrLine>, OcrError> {
+        use ocrs::{ImageSource, TextItem};
+
+        let rgb = image::DynamicImage::ImageRgba8(image.clone()).to_rgb8();
+        let source = ImageSource::from_bytes(rgb.as_raw(), rgb.dimensions())
+            .map_err(|e| OcrError::Image(e.to_string()))?;
+        let input = self.engine.prepare_input(source)
+            .map_err(|e| OcrError::Recognition(e.to_string()))?;
+
+        let word_rects = self.engine.detect_words(&input)
+            .map_err(|e| OcrError::Recognition(e.to_string()))?;
+        let line_rects = self.engine.find_text_lines(&input, &word_rects);
+        let lines = self.engine.recognize_text(&input, &line_rects)
+            .map_err(|e| OcrError::Recognition(e.to_string()))?;
+
+        Ok(lines
+            .into_iter()
+            .flatten()
+            .map(|line| {
+                let rect = line.bounding_rect();
+                OcrLine {
+                    text: line.to_string(),
+                    bbox: BoundingBox::from_edges(rect.left(), rect.top(), rect.right(), rect.bottom()),
+                    words: line
+                        .words()
+                        .map(|word| {
+                            let rect = word.bounding_rect();
+                            OcrWord {
+                                text: word.to_string(),
+                                bbox: BoundingBox::from_edges(rect.left(), rect.top(), rect.right(), rect.bottom()),
+                            }
+                        })
+                        .collect(),
+                }
+            })
+            .collect())
+    }
+}
+
+/// Run OCR on an image, downscaling very large captures and mapping boxes back
+pub fn recognize_image(recognizer: &dyn TextRecognizer, image: &RgbaImage) -> Result<OcrResult, OcrError> {
+    let (width, height) = image.dimensions();
+
+    let resized = crate::capture::fit_to_max_dimension(image, Some(MAX_OCR_DIMENSION));
+    let factor = resized
+        .as_ref()
+        .map(|small| width as f64 / small.width() as f64)
+        .unwrap_or(1.0);
+
+    let mut lines = recognizer.recognize(resized.as_ref().unwrap_or(image))?;
+
+    if factor != 1.0 {
+        for line in &mut lines {
+            line.bbox = line.bbox.scaled(factor);
+            for word in &mut line.words {
+                word.bbox = word.bbox.scaled(factor);
+            }
+        }
+    }
+
+    debug!("🔎 OCR found {} lines in {}x{} image", lines.len(), width, height);
+    Ok(OcrResult { width, height, lines })
+}
+
+/// Run OCR on an image file
+pub fn recognize_file(recognizer: &dyn TextRecognizer, path: &Path) -> Result<OcrResult, OcrError> {
+    let image = image::open(path)
+        .map_err(|e| OcrError::Image(e.to_string()))?
+        .to_rgba8();
+    recognize_image(recognizer, &image)
+}
+
+/// Capture path with its OCR text, OCR failures don't fail the capture
+#[derive(Serialize, Debug, Clone)]
+pub struct CaptureAnalysis {
+    /// History id, set once the capture is indexed
+    pub capture_id: Option<String>,
+    pub image_path: String,
+    pub text: String,
+    pub context: Option<String>,
+    pub ocr: Option<OcrResult>,
+    pub ocr_error: Option<String>,
+}
+
+/// Analyze a capture file with the given recognizer
+pub fn analyze_capture(recognizer: Result<Arc<dyn TextRecognizer>, OcrError>, image_path: String) -> CaptureAnalysis {
+    let result = recognizer.and_then(|r| recognize_file(r.as_ref(), Path::new(&image_path)));
+
+    match result {
+        Ok(ocr) => CaptureAnalysis {
+            capture_id: None,
+            image_path,
+            text: ocr.text(),
+            context: ocr.to_context(DEFAULT_CONTEXT_CHARS),
+            ocr: Some(ocr),
+            ocr_error: None,
+        },
+        Err(e) => {
+            warn!("🔎 OCR unavailable for capture: {}", e);
+            CaptureAnalysis {
+                capture_id: None,
+                image_path,
+                text: String::new(),
+                context: None,
+                ocr: None,
+                ocr_error: Some(e.to_string()),
+            }
+        }
+    }
+}
+
+/// Lazily loaded OCR engine shared by all commands
+#[derive(Default)]
+pub struct OcrState(Mutex<Option<Arc<dyn TextRecognizer>>>);
+
+impl OcrState {
+    pub fn recognizer(&self, app: &AppHandle) -> Result<Arc<dyn TextRecognizer>, OcrError> {
+        let mut guard = self.0.lock().unwrap();
+        if let Some(recognizer) = guard.as_ref() {
+            return Ok(recognizer.clone());
+        }
+
+        let recognizer: Arc<dyn TextRecognizer> = Arc::new(OcrsRecognizer::load(&models_dir(app))?);
+        *guard = Some(recognizer.clone());
+        Ok(recognizer)
+    }
+}
+
+/// Locate the OCR models: env override, bundled resources (`bundle.resources`), then app data
+fn models_dir(app: &AppHandle) -> PathBuf {
+    if let Ok(dir) = std::env::var(MODELS_DIR_ENV) {
+        return PathBuf::from(dir);
+    }
+
+    let candidates = [
+        app.path().resource_dir().ok().map(|dir| dir.join("ocr")),
+        app.path().app_data_dir().ok().map(|dir| dir.join("ocr")),
+    ];
+
+    candidates
+        .iter()
+        .flatten()
+        .find(|dir| dir.join(DETECTION_MODEL).exists())
+        .or(candidates.iter().flatten().next())
+        .cloned()
+        .unwrap_or_else(|| PathBuf::from("ocr"))
+}
+
+/// Extract text with bounding boxes from a capture
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+pub async fn ocr_capture(app: AppHandle, image_path: String) -> Result<OcrResult, String> {
+    let recognizer = app.state::<OcrState>().recognizer(&app).map_err(|e| e.to_string())?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        recognize_file(recognizer.as_ref(), Path::new(&image_path))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/ocr")
+            .join(name)
+    }
+
+    /// Reports one line covering the non-white pixels of the image
+    struct InkRecognizer;
+
+    impl TextRecognizer for InkRecognizer {
+        fn recognize(&self, image: &RgbaImage) -> Result<Vec<OcrLine>, OcrError> {
+            let ink: Vec<(u32, u32)> = image
+                .enumerate_pixels()
+                .filter(|(_, _, p)| p.0[0] < 128)
+                .map(|(x, y, _)| (x, y))
+                .collect();
+
+            if ink.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let min_x = ink.iter().map(|p| p.0).min().unwrap();
+            let max_x = ink.iter().map(|p| p.0).max().unwrap();
+            let min_y = ink.iter().map(|p| p.1).min().unwrap();
+            let max_y = ink.iter().map(|p| p.1).max().unwrap();
+            let bbox = BoundingBox { x: min_x, y: min_y, width: max_x - min_x + 1, height: max_y - min_y + 1 };
+
+            Ok(vec![OcrLine {
+                text: format!("{}x{}", image.width(), image.height()),
+                bbox,
+                words: Vec::new(),
+            }])
+        }
+    }
+
+    fn line(text: &str) -> OcrLine {
+        OcrLine {
+            text: text.to_string(),
+            bbox: BoundingBox { x: 0, y: 0, width: 10, height: 10 },
+            words: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_recognize_fixture_boxes() {
+        let result = recognize_file(&InkRecognizer, &fixture("invoice.png")).unwrap();
+        assert_eq!((result.width, result.height), (480, 140));
+        assert_eq!(result.lines.len(), 1);
+
+        // Ink of both text lines sits inside the image, left margin is 20px
+        let bbox = result.lines[0].bbox;
+        assert!(bbox.x >= 18 && bbox.x <= 24);
+        assert!(bbox.y + bbox.height <= 140);
+    }
+
+    #[test]
+    fn test_blank_fixture_has_no_text() {
+        let result = recognize_file(&InkRecognizer, &fixture("blank.png")).unwrap();
+        assert!(result.lines.is_empty());
+        assert_eq!(result.to_context(DEFAULT_CONTEXT_CHARS), None);
+    }
+
+    #[test]
+    fn test_large_images_are_downscaled_and_boxes_mapped_back() {
+        let mut image = RgbaImage::from_pixel(5120, 100, image::Rgba([255, 255, 255, 255]));
+        for x in 1000..2000 {
+            for y in 40..60 {
+                image.put_pixel(x, y, image::Rgba([0, 0, 0, 255]));
+            }
+        }
+
+        let result = recognize_image(&InkRecognizer, &image).unwrap();
+        assert_eq!(result.lines[0].text, "2560x50");
+
+        let bbox = result.lines[0].bbox;
+        assert!((bbox.x as i64 - 1000).abs() <= 4);
+        assert!((bbox.width as i64 - 1000).abs() <= 8);
+    }
+
+    #[test]
+    fn test_context_truncates_on_line_boundary() {
+        let result = OcrResult {
+            width: 100,
+            height: 100,
+            lines: vec![line("first line"), line("  "), line("second line"), line("third line")],
+        };
+
+        assert_eq!(result.text(), "first line\n  \nsecond line\nthird line");
+
+        let context = result.to_context(25).unwrap();
+        assert!(context.ends_with("first line\nsecond line"));
+        assert!(!context.contains("third"));
+    }
+
+    #[test]
+    fn test_analyze_capture_keeps_path_when_ocr_fails() {
+        let path = fixture("invoice.png").to_string_lossy().to_string();
+
+        let analysis = analyze_capture(Ok(Arc::new(InkRecognizer)), path.clone());
+        assert_eq!(analysis.image_path, path);
+        assert_eq!(analysis.text, "480x140");
+        assert!(analysis.context.unwrap().contains("480x140"));
+
+        let failed = analyze_capture(Err(OcrError::ModelsNotFound("/nowhere".into())), path.clone());
+        assert_eq!(failed.image_path, path);
+        assert!(failed.ocr.is_none());
+        assert!(failed.ocr_error.unwrap().contains("/nowhere"));
+    }
+
+    #[test]
+    fn test_ocrs_engine_on_fixture() {
+        // Bundled models, fetched by fetch-ocr-models.sh
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/ocr");
+        let recognizer = OcrsRecognizer::load(&dir).expect("OCR models missing, run ./fetch-ocr-models.sh");
+        let result = recognize_file(&recognizer, &fixture("invoice.png")).unwrap();
+        let text = result.text().to_lowercase();
+        assert!(text.contains("invoice"));
+        assert!(text.contains("example.com"));
+    }
+}
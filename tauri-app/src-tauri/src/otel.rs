@@ -0,0 +1,244 @@
+// src-tauri/src/otel.rs
+//! 🔭 OpenTelemetry export (feature `otel`)
+//!
+//! Features:
+//! - OTLP/HTTP exporter layer for the backend spans, meant for a local collector
+//! - Endpoint, service name and sampling ratio from `NUMA_OTEL_*`
+//! - Only the IPC command and OpenAI spans: names, durations and payload sizes, never log events
+//! - Only with the user's metrics consent (`consent.rs`)
+//! - Forced off in stealth (privacy) mode: no new spans, buffered ones are dropped
+
+use crate::metrics;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::trace::{Sampler, SdkTracerProvider, SpanData, SpanExporter};
+use opentelemetry_sdk::Resource;
+use once_cell::sync::OnceCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::Subscriber;
+use tracing_subscriber::filter::dynamic_filter_fn;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+pub const ENDPOINT_ENV: &str = "NUMA_OTEL_ENDPOINT";
+pub const SERVICE_NAME_ENV: &str = "NUMA_OTEL_SERVICE_NAME";
+/// Share of traces kept, between 0 and 1
+pub const SAMPLE_RATIO_ENV: &str = "NUMA_OTEL_SAMPLE_RATIO";
+
+/// Default OTLP/HTTP traces endpoint of a local collector
+const DEFAULT_ENDPOINT: &str = "http://localhost:4318/v1/traces";
+const DEFAULT_SERVICE_NAME: &str = "numa";
+
+/// Mirror of `StealthState`, read by the subscriber which has no app handle
+static PRIVACY_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Mirror of the metrics consent, off until `consent::init` reads it
+static CONSENT: AtomicBool = AtomicBool::new(false);
+
+/// Kept alive for the whole run, dropping it stops the batch exporter
+static PROVIDER: OnceCell<SdkTracerProvider> = OnceCell::new();
+
+/// Outcome of `layer`, logged once the subscriber is installed
+static STATUS: OnceCell<Result<OtelConfig, String>> = OnceCell::new();
+
+/// Called whenever stealth mode is turned on or off
+pub fn set_privacy_mode(active: bool) {
+    PRIVACY_MODE.store(active, Ordering::SeqCst);
+}
+
+/// Called whenever the metrics consent is loaded or changed
+pub fn set_consent(given: bool) {
+    CONSENT.store(given, Ordering::SeqCst);
+}
+
+pub fn export_allowed() -> bool {
+    CONSENT.load(Ordering::SeqCst) && !PRIVACY_MODE.load(Ordering::SeqCst)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OtelConfig {
+    pub endpoint: String,
+    pub service_name: String,
+    pub sample_ratio: f64,
+}
+
+impl OtelConfig {
+    pub fn from_env() -> Self {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let non_empty = |name| var(name).filter(|value| !value.trim().is_empty());
+        Self {
+            endpoint: non_empty(ENDPOINT_ENV).unwrap_or_else(|| DEFAULT_ENDPOINT.to_string()),
+            service_name: non_empty(SERVICE_NAME_ENV).unwrap_or_else(|| DEFAULT_SERVICE_NAME.to_string()),
+            sample_ratio: non_empty(SAMPLE_RATIO_ENV)
+                .and_then(|ratio| ratio.trim().parse::<f64>().ok())
+                .filter(|ratio| ratio.is_finite())
+                .map_or(1.0, |ratio| ratio.clamp(0.0, 1.0)),
+        }
+    }
+
+    /// Children follow their parent, roots are kept with `sample_ratio`
+    fn sampler(&self) -> Sampler {
+        Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(self.sample_ratio)))
+    }
+}
+
+/// Drops batches flushed while privacy mode is on or consent is withdrawn, for spans started before
+#[derive(Debug)]
+struct PrivacyGuard<E>(E);
+
+impl<E: SpanExporter> SpanExporter for PrivacyGuard<E> {
+    async fn export(&self, batch: Vec<SpanData>) -> OTelSdkResult {
+        if !export_allowed() {
+            return Ok(());
+        }
+        self.0.export(batch).await
+    }
+
+    fn shutdown(&mut self) -> OTelSdkResult {
+        self.0.shutdown()
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.0.set_resource(resource);
+    }
+}
+
+/// Spans exported: IPC commands and OpenAI calls, with consent and privacy mode off
+fn exported(meta: &tracing::Metadata<'_>) -> bool {
+    meta.is_span()
+        && (meta.target() == metrics::IPC_TARGET || meta.target() == metrics::OPENAI_TARGET)
+        && export_allowed()
+}
+
+fn tracer_provider<E: SpanExporter + 'static>(exporter: E, config: &OtelConfig) -> SdkTracerProvider {
+    SdkTracerProvider::builder()
+        .with_batch_exporter(PrivacyGuard(exporter))
+        .with_sampler(config.sampler())
+        .with_resource(
+            Resource::builder()
+                .with_service_name(config.service_name.clone())
+                .with_attribute(opentelemetry::KeyValue::new("service.version", env!("CARGO_PKG_VERSION")))
+                .build(),
+        )
+        .build()
+}
+
+fn tracing_layer<S>(provider: &SdkTracerProvider) -> impl Layer<S> + Send + Sync
+where
+    S: Subscriber + for<'a> LookupSpan<'a> + Send + Sync,
+{
+    // Dynamic: privacy mode changes at runtime, callsite interest must not be cached
+    tracing_opentelemetry::layer()
+        .with_tracer(provider.tracer(DEFAULT_SERVICE_NAME))
+        .with_filter(dynamic_filter_fn(|meta, _| exported(meta)))
+}
+
+/// OTLP layer for `build_subscriber`, `None` when the exporter cannot be built
+pub fn layer<S>(config: OtelConfig) -> Option<impl Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a> + Send + Sync,
+{
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(&config.endpoint)
+        .build();
+
+    match exporter {
+        Ok(exporter) => {
+            let provider = tracer_provider(exporter, &config);
+            let layer = tracing_layer(&provider);
+            let _ = PROVIDER.set(provider);
+            let _ = STATUS.set(Ok(config));
+            Some(layer)
+        }
+        Err(e) => {
+            let _ = STATUS.set(Err(e.to_string()));
+            None
+        }
+    }
+}
+
+/// Exporter config, or why it is disabled, `None` before `layer`
+pub fn status() -> Option<&'static Result<OtelConfig, String>> {
+    STATUS.get()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry_sdk::trace::InMemorySpanExporter;
+    use std::collections::HashMap;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    fn config(vars: &[(&str, &str)]) -> OtelConfig {
+        let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        OtelConfig::from_vars(|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn test_config_from_env() {
+        assert_eq!(
+            config(&[]),
+            OtelConfig {
+                endpoint: DEFAULT_ENDPOINT.to_string(),
+                service_name: DEFAULT_SERVICE_NAME.to_string(),
+                sample_ratio: 1.0,
+            }
+        );
+
+        let custom = config(&[
+            (ENDPOINT_ENV, "http://127.0.0.1:4318/v1/traces"),
+            (SERVICE_NAME_ENV, "numa-dev"),
+            (SAMPLE_RATIO_ENV, "0.25"),
+        ]);
+        assert_eq!(custom.endpoint, "http://127.0.0.1:4318/v1/traces");
+        assert_eq!(custom.service_name, "numa-dev");
+        assert_eq!(custom.sample_ratio, 0.25);
+
+        assert_eq!(config(&[(SAMPLE_RATIO_ENV, "7")]).sample_ratio, 1.0);
+        assert_eq!(config(&[(SAMPLE_RATIO_ENV, "-1")]).sample_ratio, 0.0);
+        assert_eq!(config(&[(SAMPLE_RATIO_ENV, "NaN")]).sample_ratio, 1.0);
+        assert_eq!(config(&[(SERVICE_NAME_ENV, " ")]).service_name, DEFAULT_SERVICE_NAME);
+    }
+
+    #[test]
+    fn test_only_command_spans_are_exported_with_consent_outside_privacy_mode() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = tracer_provider(exporter.clone(), &config(&[]));
+        let subscriber = tracing_subscriber::registry().with(tracing_layer(&provider));
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        set_consent(false);
+        set_privacy_mode(false);
+        tracing::info_span!(target: "ipc", "otel_test_no_consent").in_scope(|| {});
+
+        set_consent(true);
+        tracing::info_span!(target: "ipc", "otel_test_command").in_scope(|| {
+            tracing::info!(body = "secret", "not exported");
+        });
+        tracing::info_span!("otel_test_other").in_scope(|| {});
+
+        set_privacy_mode(true);
+        tracing::info_span!(target: "ipc", "otel_test_stealth").in_scope(|| {});
+
+        // Started before stealth, flushed during it
+        set_privacy_mode(false);
+        let late = tracing::info_span!(target: "ipc", "otel_test_late");
+        provider.force_flush().unwrap();
+        let before_stealth = exporter.get_finished_spans().unwrap();
+        set_privacy_mode(true);
+        drop(late);
+        provider.force_flush().unwrap();
+        set_privacy_mode(false);
+
+        let finished = exporter.get_finished_spans().unwrap();
+        assert_eq!(before_stealth.len(), finished.len());
+        let names: Vec<_> = finished.iter().map(|span| span.name.to_string()).collect();
+        assert_eq!(names, vec!["otel_test_command"]);
+        assert!(finished[0].events.is_empty(), "log events must not be exported");
+    }
+}
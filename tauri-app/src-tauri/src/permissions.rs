@@ -0,0 +1,240 @@
+// src-tauri/src/permissions.rs
+//! 🔐 Per-window IPC command permissions
+//!
+//! Features:
+//! - Policy table mapping each webview label to the commands it may invoke
+//! - Checked by the IPC middleware (`ipc.rs`) before rate limiting and validation
+//! - Unknown windows are denied everything
+//! - Denials are written to the audit log
+
+use thiserror::Error;
+
+pub const HUD: &str = "hud";
+pub const INPUT: &str = "input";
+pub const CONTEXT: &str = "context";
+pub const PANEL: &str = "panel";
+
+/// Read-only state every Numa window may query
+const SHARED: &[&str] = &[
+    "get_stealth_status",
+    "get_hud_position_and_size",
+    "get_capture_options",
+    "get_redaction_settings",
+    "get_clipboard_permission",
+    "get_chat_config",
+    "get_rate_limit_quota",
+    "get_dynamic_csp_policy",
+    "get_csp_for_context",
+    "get_backend_metrics",
+    "get_consent",
+];
+
+/// Privacy withdrawals (Do Not Track), every window may only turn collection off
+const PRIVACY_WITHDRAWAL: &[&str] = &["revoke_consent"];
+
+/// HUD bar: window management, stealth and captures
+const HUD_COMMANDS: &[&str] = &[
+    "close_all_windows",
+    "start_window_dragging",
+    "force_hud_redraw",
+    "resize_window",
+    "panel_show",
+    "panel_hide",
+    "input_show",
+    "input_hide",
+    "context_show",
+    "context_hide",
+    "toggle_stealth_cmd",
+    "test_stealth_manual",
+    "capture_screen",
+    "capture_and_analyze",
+    "get_image_as_base64",
+    "list_windows",
+    "capture_window",
+    "init_ns_panel",
+    "init_context_ns_panel",
+    "init_input_ns_panel",
+    "show_app",
+    "hide_app",
+    "show_context_panel",
+    "hide_context_panel",
+    "show_input_panel",
+    "hide_input_panel",
+];
+
+/// Captures the chat may show or attach
+const CAPTURE_BROWSING: &[&str] = &[
+    "list_captures",
+    "search_captures",
+    "get_capture_thumbnail",
+    "get_image_as_base64",
+];
+
+/// Answer copy buttons
+const ANSWER_COPY: &[&str] = &["copy_answer", "copy_code_block"];
+
+/// Input window: chat, attachments and its own placement
+const INPUT_COMMANDS: &[&str] = &[
+    "input_hide",
+    "input_resize",
+    "input_undock",
+    "input_dock",
+    "check_snap_distance",
+    "start_input_dragging",
+    "start_chat",
+    "chat_with_openai",
+    "attach_capture_to_chat",
+    "add_attachments",
+    "list_attachments",
+    "remove_attachment",
+    "read_clipboard_context",
+    "ocr_capture",
+    "redact_capture",
+];
+
+/// Context window: its own placement only
+const CONTEXT_COMMANDS: &[&str] = &[
+    "context_hide",
+    "context_resize",
+    "context_undock",
+    "context_dock",
+    "check_context_snap_distance",
+    "start_context_dragging",
+];
+
+/// Settings panel: secrets, settings, permissions, logs and crash reports
+const PANEL_COMMANDS: &[&str] = &[
+    "panel_hide",
+    "toggle_stealth_cmd",
+    "secure_store",
+    "secure_load",
+    "secure_delete",
+    "store_openai_key",
+    "set_capture_options",
+    "set_redaction_settings",
+    "set_clipboard_permission",
+    "get_clipboard_audit",
+    "get_audit_log",
+    "delete_capture",
+    "list_crash_reports",
+    "export_crash_report",
+    "get_crash_reporting_status",
+    "set_consent",
+    "get_log_levels",
+    "set_log_level",
+    "subscribe_logs",
+    "unsubscribe_logs",
+    "export_diagnostics",
+];
+
+/// Commands allowed for one window, as a union of groups
+pub struct WindowPolicy {
+    pub window: &'static str,
+    pub groups: &'static [&'static [&'static str]],
+}
+
+impl WindowPolicy {
+    pub fn allows(&self, command: &str) -> bool {
+        self.groups.iter().any(|group| group.contains(&command))
+    }
+}
+
+pub const POLICY: &[WindowPolicy] = &[
+    WindowPolicy { window: HUD, groups: &[SHARED, PRIVACY_WITHDRAWAL, HUD_COMMANDS] },
+    WindowPolicy { window: INPUT, groups: &[SHARED, PRIVACY_WITHDRAWAL, INPUT_COMMANDS, CAPTURE_BROWSING, ANSWER_COPY] },
+    WindowPolicy { window: CONTEXT, groups: &[SHARED, PRIVACY_WITHDRAWAL, CONTEXT_COMMANDS, CAPTURE_BROWSING, ANSWER_COPY] },
+    WindowPolicy { window: PANEL, groups: &[SHARED, PRIVACY_WITHDRAWAL, PANEL_COMMANDS, CAPTURE_BROWSING] },
+];
+
+#[derive(Error, Debug, PartialEq)]
+pub enum PermissionError {
+    #[error("Window '{window}' is not allowed to call '{command}'")]
+    Denied { window: String, command: String },
+}
+
+pub fn is_allowed(window: &str, command: &str) -> bool {
+    POLICY
+        .iter()
+        .find(|policy| policy.window == window)
+        .is_some_and(|policy| policy.allows(command))
+}
+
+/// Fail when `window` may not invoke `command`
+pub fn check(window: &str, command: &str) -> Result<(), PermissionError> {
+    if is_allowed(window, command) {
+        Ok(())
+    } else {
+        Err(PermissionError::Denied {
+            window: window.to_string(),
+            command: command.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Expected decision for sensitive commands, per window: hud, input, context, panel
+    const MATRIX: &[(&str, [bool; 4])] = &[
+        ("secure_load", [false, false, false, true]),
+        ("secure_store", [false, false, false, true]),
+        ("store_openai_key", [false, false, false, true]),
+        ("close_all_windows", [true, false, false, false]),
+        ("toggle_stealth_cmd", [true, false, false, true]),
+        ("capture_screen", [true, false, false, false]),
+        ("capture_window", [true, false, false, false]),
+        ("get_image_as_base64", [true, true, true, true]),
+        ("start_chat", [false, true, false, false]),
+        ("read_clipboard_context", [false, true, false, false]),
+        ("set_clipboard_permission", [false, false, false, true]),
+        ("get_audit_log", [false, false, false, true]),
+        ("export_crash_report", [false, false, false, true]),
+        ("set_consent", [false, false, false, true]),
+        ("revoke_consent", [true, true, true, true]),
+        ("set_log_level", [false, false, false, true]),
+        ("subscribe_logs", [false, false, false, true]),
+        ("export_diagnostics", [false, false, false, true]),
+        ("delete_capture", [false, false, false, true]),
+        ("copy_answer", [false, true, true, false]),
+        ("input_resize", [false, true, false, false]),
+        ("context_resize", [false, false, true, false]),
+        ("get_stealth_status", [true, true, true, true]),
+    ];
+
+    #[test]
+    fn test_policy_matrix() {
+        for (command, expected) in MATRIX {
+            for (window, allowed) in [HUD, INPUT, CONTEXT, PANEL].iter().zip(expected) {
+                assert_eq!(is_allowed(window, command), *allowed, "{} calling {}", window, command);
+            }
+        }
+    }
+
+    #[test]
+    fn test_unknown_window_is_denied() {
+        assert!(!is_allowed("devtools", "get_stealth_status"));
+        assert!(!is_allowed("", "capture_screen"));
+
+        let err = check("input", "secure_load").unwrap_err();
+        assert_eq!(err.to_string(), "Window 'input' is not allowed to call 'secure_load'");
+    }
+
+    #[test]
+    fn test_policy_matches_the_registry() {
+        let registered: Vec<&str> = crate::ipc::command_names().collect();
+
+        for policy in POLICY {
+            for command in policy.groups.iter().flat_map(|group| group.iter()) {
+                assert!(registered.contains(command), "{} allows unknown command {}", policy.window, command);
+            }
+        }
+        for command in registered {
+            assert!(
+                POLICY.iter().any(|policy| policy.allows(command)),
+                "{} is registered but no window may call it",
+                command
+            );
+        }
+    }
+}
@@ -0,0 +1,478 @@
+// src-tauri/src/rate_limit.rs
+//! ⏱️ Token-bucket rate limiting for IPC commands
+//!
+//! Features:
+//! - One bucket per command (burst capacity + continuous refill)
+//! - Limits resolved per command, then per rate class, then default
+//! - Limits loaded from `rate_limits.json`, overridable from the app config directory
+//! - No global lock on the hot path: the bucket map is read-locked, each bucket has its own lock
+//! - Remaining quota exposed to the frontend
+//! - Injectable clock for deterministic tests
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+use crate::validation::ValidationError;
+
+/// Limits shipped with the app
+const DEFAULT_CONFIG: &str = include_str!("../rate_limits.json");
+
+/// File name looked up in the app config directory
+pub const CONFIG_FILE: &str = "rate_limits.json";
+
+/// Upper bound accepted for burst and refill values
+const MAX_LIMIT: u32 = 100_000;
+
+/// Monotonic time source
+pub trait Clock: Send + Sync {
+    /// Time elapsed since an arbitrary fixed origin
+    fn now(&self) -> Duration;
+}
+
+/// Real clock based on `Instant`
+pub struct SystemClock(Instant);
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self(Instant::now())
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.0.elapsed()
+    }
+}
+
+/// Manually advanced clock for tests
+#[derive(Default)]
+pub struct MockClock(AtomicU64);
+
+impl MockClock {
+    pub fn advance(&self, by: Duration) {
+        self.0.fetch_add(by.as_nanos() as u64, Ordering::SeqCst);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Duration {
+        Duration::from_nanos(self.0.load(Ordering::SeqCst))
+    }
+}
+
+/// Limits for one command
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct BucketConfig {
+    /// Requests allowed at once
+    pub burst: u32,
+    /// Tokens added back per minute
+    pub refill_per_minute: u32,
+}
+
+impl BucketConfig {
+    fn refill_per_ms(&self) -> f64 {
+        self.refill_per_minute as f64 / 60_000.0
+    }
+
+    fn validate(&self, name: &str) -> Result<(), String> {
+        for (field, value) in [("burst", self.burst), ("refill_per_minute", self.refill_per_minute)] {
+            if !(1..=MAX_LIMIT).contains(&value) {
+                return Err(format!("{}: {} must be between 1 and {}", name, field, MAX_LIMIT));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Full limiter configuration
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RateLimitConfig {
+    pub default: BucketConfig,
+    /// Limits shared by every command of a rate class (see `ipc::RateClass`)
+    #[serde(default)]
+    pub classes: HashMap<String, BucketConfig>,
+    #[serde(default)]
+    pub commands: HashMap<String, BucketConfig>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self::from_json(DEFAULT_CONFIG).expect("embedded rate_limits.json is valid")
+    }
+}
+
+impl RateLimitConfig {
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let config: Self = serde_json::from_str(json).map_err(|e| format!("Invalid rate limit config: {}", e))?;
+        config.default.validate("default")?;
+        for (command, bucket) in config.classes.iter().chain(&config.commands) {
+            bucket.validate(command)?;
+        }
+        Ok(config)
+    }
+
+    /// Entries from `overrides` replace the ones in `self`
+    pub fn merge(mut self, overrides: RateLimitConfig) -> Self {
+        self.default = overrides.default;
+        self.classes.extend(overrides.classes);
+        self.commands.extend(overrides.commands);
+        self
+    }
+
+    pub fn for_command(&self, command: &str) -> BucketConfig {
+        self.resolve(command, None)
+    }
+
+    /// Command entry first, then its class, then the default
+    pub fn resolve(&self, command: &str, class: Option<&str>) -> BucketConfig {
+        self.commands
+            .get(command)
+            .or_else(|| class.and_then(|class| self.classes.get(class)))
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+/// Remaining quota for a command
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Quota {
+    pub command: String,
+    pub remaining: u32,
+    pub burst: u32,
+    pub refill_per_minute: u32,
+    /// Wait before the next request is accepted, 0 when one is available
+    pub retry_after_ms: u64,
+}
+
+struct BucketState {
+    tokens: f64,
+    updated_at: Duration,
+}
+
+struct Bucket {
+    config: BucketConfig,
+    state: Mutex<BucketState>,
+}
+
+impl Bucket {
+    fn new(config: BucketConfig, now: Duration) -> Self {
+        Self {
+            config,
+            state: Mutex::new(BucketState { tokens: config.burst as f64, updated_at: now }),
+        }
+    }
+
+    fn refill(&self, state: &mut BucketState, now: Duration) {
+        let elapsed_ms = now.saturating_sub(state.updated_at).as_secs_f64() * 1000.0;
+        state.tokens = (state.tokens + elapsed_ms * self.config.refill_per_ms()).min(self.config.burst as f64);
+        state.updated_at = now;
+    }
+
+    fn quota(&self, command: &str, state: &BucketState) -> Quota {
+        let missing = (1.0 - state.tokens).max(0.0);
+        Quota {
+            command: command.to_string(),
+            remaining: state.tokens.floor() as u32,
+            burst: self.config.burst,
+            refill_per_minute: self.config.refill_per_minute,
+            retry_after_ms: (missing / self.config.refill_per_ms()).ceil() as u64,
+        }
+    }
+
+    /// Take one token if available
+    fn try_acquire(&self, command: &str, now: Duration) -> Result<Quota, Quota> {
+        let mut state = self.state.lock().unwrap();
+        self.refill(&mut state, now);
+
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            Ok(self.quota(command, &state))
+        } else {
+            Err(self.quota(command, &state))
+        }
+    }
+
+    fn peek(&self, command: &str, now: Duration) -> Quota {
+        let mut state = self.state.lock().unwrap();
+        self.refill(&mut state, now);
+        self.quota(command, &state)
+    }
+
+    fn is_full(&self, now: Duration) -> bool {
+        let mut state = self.state.lock().unwrap();
+        self.refill(&mut state, now);
+        state.tokens >= self.config.burst as f64
+    }
+}
+
+/// Per-command token buckets
+pub struct RateLimiter {
+    clock: Arc<dyn Clock>,
+    config: RwLock<RateLimitConfig>,
+    buckets: RwLock<HashMap<String, Arc<Bucket>>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            clock,
+            config: RwLock::new(config),
+            buckets: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn bucket(&self, command: &str, class: Option<&str>) -> Arc<Bucket> {
+        if let Some(bucket) = self.buckets.read().unwrap().get(command) {
+            return bucket.clone();
+        }
+
+        // First call for this command: take the write lock once
+        let config = self.config.read().unwrap().resolve(command, class);
+        let now = self.clock.now();
+        self.buckets
+            .write()
+            .unwrap()
+            .entry(command.to_string())
+            .or_insert_with(|| Arc::new(Bucket::new(config, now)))
+            .clone()
+    }
+
+    /// Consume one request for `command`
+    pub fn check(&self, command: &str) -> Result<Quota, ValidationError> {
+        self.check_in(command, None)
+    }
+
+    /// Consume one request for `command`, limited by its class unless configured explicitly
+    pub fn check_in(&self, command: &str, class: Option<&str>) -> Result<Quota, ValidationError> {
+        match self.bucket(command, class).try_acquire(command, self.clock.now()) {
+            Ok(quota) => {
+                debug!("✅ Rate limit check passed for '{}': {}/{} left", command, quota.remaining, quota.burst);
+                Ok(quota)
+            }
+            Err(quota) => {
+                warn!("🚨 Rate limit exceeded for command '{}': retry in {}ms", command, quota.retry_after_ms);
+                Err(ValidationError::RateLimitExceeded {
+                    command: command.to_string(),
+                    retry_after_ms: quota.retry_after_ms,
+                })
+            }
+        }
+    }
+
+    /// Remaining quota without consuming anything
+    pub fn remaining(&self, command: &str) -> Quota {
+        self.remaining_in(command, None)
+    }
+
+    pub fn remaining_in(&self, command: &str, class: Option<&str>) -> Quota {
+        self.bucket(command, class).peek(command, self.clock.now())
+    }
+
+    /// Replace the configuration, buckets restart full
+    pub fn set_config(&self, config: RateLimitConfig) {
+        *self.config.write().unwrap() = config;
+        self.buckets.write().unwrap().clear();
+    }
+
+    /// Drop full buckets, they are recreated on demand
+    pub fn cleanup(&self) {
+        let now = self.clock.now();
+        let mut buckets = self.buckets.write().unwrap();
+        buckets.retain(|_, bucket| !bucket.is_full(now));
+        debug!("🧹 Rate limiter cleaned up, {} commands tracked", buckets.len());
+    }
+}
+
+static LIMITER: Lazy<RateLimiter> =
+    Lazy::new(|| RateLimiter::new(RateLimitConfig::default(), Arc::new(SystemClock::default())));
+
+/// Limiter used by `validate_and_rate_limit`
+pub fn global() -> &'static RateLimiter {
+    &LIMITER
+}
+
+/// Apply `rate_limits.json` from the app config directory on top of the defaults
+pub fn load_overrides(config_dir: &Path) {
+    let path = config_dir.join(CONFIG_FILE);
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return;
+    };
+
+    match RateLimitConfig::from_json(&content) {
+        Ok(overrides) => {
+            global().set_config(RateLimitConfig::default().merge(overrides));
+            info!("⏱️ Rate limits loaded from {}", path.display());
+        }
+        Err(e) => warn!("⏱️ Ignoring {}: {}", path.display(), e),
+    }
+}
+
+fn quota_for(command: &str) -> Quota {
+    let class = crate::ipc::spec(command).and_then(|spec| spec.rate.key());
+    global().remaining_in(command, class)
+}
+
+/// Remaining quota of every registered command
+pub fn quotas() -> Vec<Quota> {
+    crate::ipc::command_names().map(quota_for).collect()
+}
+
+/// Remaining quota for one command, or for every registered command
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+pub fn get_rate_limit_quota(command: Option<String>) -> Result<Vec<Quota>, String> {
+    match command {
+        // Unknown names would each get a bucket that is never used
+        Some(command) if crate::ipc::spec(&command).is_none() => Err(format!("Unknown command: {}", command)),
+        Some(command) => Ok(vec![quota_for(&command)]),
+        None => Ok(quotas()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(burst: u32, refill_per_minute: u32) -> (RateLimiter, Arc<MockClock>) {
+        let clock = Arc::new(MockClock::default());
+        let config = RateLimitConfig {
+            default: BucketConfig { burst, refill_per_minute },
+            classes: HashMap::from([("chat".to_string(), BucketConfig { burst: 2, refill_per_minute: 2 })]),
+            commands: HashMap::from([("slow".to_string(), BucketConfig { burst: 1, refill_per_minute: 1 })]),
+        };
+        (RateLimiter::new(config, clock.clone()), clock)
+    }
+
+    #[test]
+    fn test_quota_of_unknown_command_is_rejected() {
+        assert!(get_rate_limit_quota(Some("not_a_command".into())).is_err());
+
+        let quota = get_rate_limit_quota(Some("chat_with_openai".into())).unwrap();
+        assert_eq!(quota.len(), 1);
+        assert_eq!(get_rate_limit_quota(None).unwrap().len(), crate::ipc::command_names().count());
+    }
+
+    #[test]
+    fn test_burst_then_reject() {
+        let (limiter, _clock) = limiter(3, 60);
+
+        for expected in [2, 1, 0] {
+            assert_eq!(limiter.check("cmd").unwrap().remaining, expected);
+        }
+        assert!(matches!(limiter.check("cmd"), Err(ValidationError::RateLimitExceeded { retry_after_ms: 1000, .. })));
+        assert_eq!(limiter.remaining("cmd").retry_after_ms, 1000);
+    }
+
+    #[test]
+    fn test_refill_over_time_is_capped_at_burst() {
+        let (limiter, clock) = limiter(3, 60); // one token per second
+
+        for _ in 0..3 {
+            limiter.check("cmd").unwrap();
+        }
+        clock.advance(Duration::from_millis(999));
+        assert!(limiter.check("cmd").is_err());
+
+        clock.advance(Duration::from_millis(1));
+        assert!(limiter.check("cmd").is_ok());
+        assert!(limiter.check("cmd").is_err());
+
+        clock.advance(Duration::from_secs(3600));
+        assert_eq!(limiter.remaining("cmd").remaining, 3);
+    }
+
+    #[test]
+    fn test_commands_have_independent_buckets() {
+        let (limiter, clock) = limiter(5, 60);
+
+        assert!(limiter.check("slow").is_ok());
+        assert!(limiter.check("slow").is_err());
+        assert!(limiter.check("other").is_ok());
+
+        clock.advance(Duration::from_secs(60));
+        assert!(limiter.check("slow").is_ok());
+    }
+
+    #[test]
+    fn test_class_limits_apply_per_command() {
+        let (limiter, _clock) = limiter(5, 60);
+
+        // Each command of a class gets its own bucket sized by the class
+        for command in ["start_chat", "attach_capture_to_chat"] {
+            assert!(limiter.check_in(command, Some("chat")).is_ok());
+            assert!(limiter.check_in(command, Some("chat")).is_ok());
+            assert!(limiter.check_in(command, Some("chat")).is_err());
+        }
+
+        // Explicit command entries win over the class
+        assert_eq!(limiter.remaining_in("slow", Some("chat")).burst, 1);
+        assert_eq!(limiter.remaining_in("other", Some("unknown")).burst, 5);
+    }
+
+    #[test]
+    fn test_config_loading_and_merge() {
+        let defaults = RateLimitConfig::default();
+        for command in ["start_chat", "chat_with_openai", "secure_delete"] {
+            assert!(defaults.commands.contains_key(command), "{} missing from rate_limits.json", command);
+        }
+
+        let overrides = RateLimitConfig::from_json(
+            r#"{ "default": { "burst": 50, "refill_per_minute": 50 },
+                 "commands": { "start_chat": { "burst": 1, "refill_per_minute": 2 } } }"#,
+        )
+        .unwrap();
+        let merged = defaults.clone().merge(overrides);
+        assert_eq!(merged.for_command("start_chat"), BucketConfig { burst: 1, refill_per_minute: 2 });
+        assert_eq!(merged.for_command("secure_delete"), defaults.for_command("secure_delete"));
+        assert_eq!(merged.for_command("unknown").burst, 50);
+
+        assert!(RateLimitConfig::from_json(r#"{ "default": { "burst": 0, "refill_per_minute": 1 } }"#).is_err());
+        assert!(RateLimitConfig::from_json("not json").is_err());
+    }
+
+    #[test]
+    fn test_snap_probes_sustain_a_drag() {
+        // Polled every 100 ms while a window is dragged (InputPage, ContextPage)
+        let clock = Arc::new(MockClock::default());
+        let limiter = RateLimiter::new(RateLimitConfig::default(), clock.clone());
+
+        for command in ["check_snap_distance", "check_context_snap_distance"] {
+            // 10 s drag, polling restarted with an immediate tick every second
+            for tick in 0..100 {
+                if tick % 10 == 0 {
+                    limiter.check_in(command, Some("ui")).unwrap();
+                }
+                limiter.check_in(command, Some("ui")).unwrap();
+                clock.advance(Duration::from_millis(100));
+            }
+            assert!(limiter.remaining_in(command, Some("ui")).remaining > 0);
+        }
+    }
+
+    #[test]
+    fn test_set_config_and_cleanup() {
+        let (limiter, clock) = limiter(2, 60);
+        limiter.check("cmd").unwrap();
+        limiter.check("cmd").unwrap();
+
+        limiter.set_config(RateLimitConfig {
+            default: BucketConfig { burst: 10, refill_per_minute: 60 },
+            classes: HashMap::new(),
+            commands: HashMap::new(),
+        });
+        assert_eq!(limiter.remaining("cmd").remaining, 10);
+
+        limiter.check("cmd").unwrap();
+        limiter.cleanup();
+        assert_eq!(limiter.buckets.read().unwrap().len(), 1);
+
+        clock.advance(Duration::from_secs(1));
+        limiter.cleanup();
+        assert!(limiter.buckets.read().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,661 @@
+// src-tauri/src/redaction.rs
+//! 🙈 PII redaction before captures leave the machine
+//!
+//! Features:
+//! - Detection of emails, phone numbers, card numbers and API keys
+//! - User-defined patterns
+//! - Masking in text, blurring of matching OCR regions in images
+//! - Per-provider toggle (e.g. off for a local Ollama)
+
+use image::RgbaImage;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+use tracing::{info, debug};
+use crate::ocr::{BoundingBox, OcrResult, TextRecognizer};
+use crate::validation::{validate_input, ValidatedInput, ValidationError};
+
+/// Limits for user-defined patterns
+const MAX_CUSTOM_PATTERNS: usize = 32;
+const MAX_PATTERN_LEN: usize = 256;
+
+/// Shared redaction settings
+static REDACTION_SETTINGS: once_cell::sync::Lazy<Arc<Mutex<RedactionSettings>>> =
+    once_cell::sync::Lazy::new(|| Arc::new(Mutex::new(RedactionSettings::default())));
+
+/// Built-in detectors: (kind, regex)
+static BUILTIN_PATTERNS: once_cell::sync::Lazy<Vec<(PiiKind, Regex)>> = once_cell::sync::Lazy::new(|| {
+    [
+        (PiiKind::ApiKey, r"\bsk-(?:proj-|ant-)?[A-Za-z0-9_-]{16,}"),
+        (PiiKind::ApiKey, r"\bAKIA[0-9A-Z]{16}\b"),
+        (PiiKind::ApiKey, r"\bgh[pousr]_[A-Za-z0-9]{36,}\b"),
+        (PiiKind::ApiKey, r"\bxox[abprs]-[A-Za-z0-9-]{10,}"),
+        (PiiKind::ApiKey, r"\bAIza[0-9A-Za-z_-]{35}\b"),
+        (PiiKind::ApiKey, r"(?i)\bbearer\s+[A-Za-z0-9._~+/-]{16,}=*"),
+        (PiiKind::Email, r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b"),
+        (PiiKind::CardNumber, r"\b\d(?:[ -]?\d){12,18}\b"),
+        // International, area code in parentheses, national with a trunk 0, or 3-3-4 groups:
+        // bare digit runs, IPv4 addresses and version strings don't fit any of them
+        (PiiKind::Phone, concat!(
+            r"\+\d{1,3}[ .-]?(?:\(\d{1,4}\)[ .-]?)?\d{1,4}(?:[ .-]?\d{2,4}){2,5}\b",
+            r"|\(\d{2,4}\)[ .-]?\d{3,4}[ .-]?\d{3,4}\b",
+            r"|\b0\d(?:[ .-]?\d{2}){4}\b",
+            r"|\b\d{3}[ .-]\d{3}[ .-]\d{4}\b",
+        )),
+    ]
+    .into_iter()
+    .map(|(kind, pattern)| (kind, Regex::new(pattern).expect("valid built-in pattern")))
+    .collect()
+});
+
+/// Category of sensitive data
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiKind {
+    Email,
+    Phone,
+    CardNumber,
+    ApiKey,
+    Custom,
+}
+
+impl PiiKind {
+    fn mask(&self) -> &'static str {
+        match self {
+            PiiKind::Email => "[REDACTED:EMAIL]",
+            PiiKind::Phone => "[REDACTED:PHONE]",
+            PiiKind::CardNumber => "[REDACTED:CARD]",
+            PiiKind::ApiKey => "[REDACTED:API_KEY]",
+            PiiKind::Custom => "[REDACTED]",
+        }
+    }
+}
+
+/// User-defined pattern
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CustomPattern {
+    pub name: String,
+    pub pattern: String,
+}
+
+/// Redaction configuration
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ValidatedInput)]
+pub struct RedactionSettings {
+    /// Provider name -> redaction enabled, unknown providers are redacted
+    pub providers: HashMap<String, bool>,
+    #[validate(custom = "validate_custom_patterns")]
+    pub custom_patterns: Vec<CustomPattern>,
+    /// Blur matching regions in images (text is always masked)
+    pub blur_images: bool,
+}
+
+impl Default for RedactionSettings {
+    fn default() -> Self {
+        Self {
+            providers: HashMap::from([
+                ("openai".to_string(), true),
+                ("anthropic".to_string(), true),
+                ("ollama".to_string(), false),
+            ]),
+            custom_patterns: Vec::new(),
+            blur_images: true,
+        }
+    }
+}
+
+impl RedactionSettings {
+    pub fn is_enabled_for(&self, provider: &str) -> bool {
+        self.providers.get(provider).copied().unwrap_or(true)
+    }
+}
+
+/// `custom` validation rule: bounded number of compilable patterns
+fn validate_custom_patterns(field: &str, patterns: &[CustomPattern]) -> Result<(), ValidationError> {
+    if patterns.len() > MAX_CUSTOM_PATTERNS {
+        return Err(ValidationError::InputTooLarge {
+            field: field.to_string(),
+            max_size: MAX_CUSTOM_PATTERNS,
+        });
+    }
+
+    for custom in patterns {
+        if custom.pattern.trim().is_empty() {
+            return Err(ValidationError::EmptyField {
+                field: "pattern".to_string(),
+            });
+        }
+        if custom.pattern.len() > MAX_PATTERN_LEN {
+            return Err(ValidationError::InputTooLarge {
+                field: "pattern".to_string(),
+                max_size: MAX_PATTERN_LEN,
+            });
+        }
+        if Regex::new(&custom.pattern).is_err() {
+            return Err(ValidationError::InvalidCharacters {
+                field: format!("pattern '{}'", custom.name),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// One detected match
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub kind: PiiKind,
+    /// Custom pattern name, or the kind for built-ins
+    pub label: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Summary of what was redacted, without the sensitive values
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct RedactionReport {
+    pub provider: String,
+    pub enabled: bool,
+    pub counts: BTreeMap<String, usize>,
+    pub text_replacements: usize,
+    pub regions_blurred: usize,
+}
+
+impl RedactionReport {
+    fn record(&mut self, findings: &[Finding]) {
+        for finding in findings {
+            *self.counts.entry(finding.label.clone()).or_insert(0) += 1;
+        }
+        self.text_replacements += findings.len();
+    }
+}
+
+/// Compiled detectors for one settings snapshot
+pub struct Redactor {
+    custom: Vec<(String, Regex)>,
+}
+
+impl Redactor {
+    pub fn new(settings: &RedactionSettings) -> Result<Self, String> {
+        let custom = settings
+            .custom_patterns
+            .iter()
+            .map(|c| {
+                Regex::new(&c.pattern)
+                    .map(|re| (c.name.clone(), re))
+                    .map_err(|e| format!("Invalid pattern '{}': {}", c.name, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { custom })
+    }
+
+    /// Find sensitive spans, overlapping matches keep the first (most specific) detector
+    pub fn find(&self, text: &str) -> Vec<Finding> {
+        let mut findings: Vec<Finding> = Vec::new();
+
+        let builtins = BUILTIN_PATTERNS.iter().map(|(kind, re)| (*kind, kind_label(*kind), re));
+        let custom = self.custom.iter().map(|(name, re)| (PiiKind::Custom, name.clone(), re));
+
+        for (kind, label, re) in builtins.chain(custom) {
+            for m in re.find_iter(text) {
+                if !is_valid_match(kind, m.as_str()) {
+                    continue;
+                }
+                if findings.iter().any(|f| m.start() < f.end && f.start < m.end()) {
+                    continue;
+                }
+                findings.push(Finding { kind, label: label.clone(), start: m.start(), end: m.end() });
+            }
+        }
+
+        findings.sort_by_key(|f| f.start);
+        findings
+    }
+
+    /// Mask every finding in `text`
+    pub fn redact_text(&self, text: &str) -> (String, Vec<Finding>) {
+        let findings = self.find(text);
+        let mut output = String::with_capacity(text.len());
+        let mut cursor = 0;
+
+        for finding in &findings {
+            output.push_str(&text[cursor..finding.start]);
+            output.push_str(finding.kind.mask());
+            cursor = finding.end;
+        }
+        output.push_str(&text[cursor..]);
+
+        (output, findings)
+    }
+
+    /// Image regions of OCR words that overlap a finding
+    pub fn sensitive_regions(&self, ocr: &OcrResult) -> Vec<BoundingBox> {
+        let mut regions = Vec::new();
+
+        for line in &ocr.lines {
+            let findings = self.find(&line.text);
+            if findings.is_empty() {
+                continue;
+            }
+
+            if line.words.is_empty() {
+                regions.push(line.bbox);
+                continue;
+            }
+
+            // Locate each word in the line text to compare byte ranges
+            let mut cursor = 0;
+            for word in &line.words {
+                let Some(offset) = line.text[cursor..].find(word.text.as_str()) else {
+                    continue;
+                };
+                let start = cursor + offset;
+                let end = start + word.text.len();
+                cursor = end;
+
+                if findings.iter().any(|f| start < f.end && f.start < end) {
+                    regions.push(word.bbox);
+                }
+            }
+        }
+
+        regions
+    }
+}
+
+fn kind_label(kind: PiiKind) -> String {
+    serde_json::to_value(kind)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_else(|| "custom".to_string())
+}
+
+/// Extra checks that regexes can't express
+fn is_valid_match(kind: PiiKind, value: &str) -> bool {
+    let digits: Vec<u32> = value.chars().filter_map(|c| c.to_digit(10)).collect();
+    match kind {
+        PiiKind::CardNumber => (13..=19).contains(&digits.len()) && luhn_valid(&digits),
+        PiiKind::Phone => (9..=15).contains(&digits.len()),
+        _ => true,
+    }
+}
+
+/// Luhn checksum used by payment card numbers
+fn luhn_valid(digits: &[u32]) -> bool {
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| {
+            if i % 2 == 1 {
+                let doubled = d * 2;
+                if doubled > 9 { doubled - 9 } else { doubled }
+            } else {
+                d
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+/// Blur regions in place, sigma follows the region height so text is unreadable
+pub fn blur_regions(image: &mut RgbaImage, regions: &[BoundingBox]) -> usize {
+    let (width, height) = image.dimensions();
+    let mut blurred = 0;
+
+    for region in regions {
+        // Pad a little so glyph edges don't survive
+        let pad = (region.height / 4).max(2);
+        let x = region.x.saturating_sub(pad).min(width);
+        let y = region.y.saturating_sub(pad).min(height);
+        let w = (region.width + pad * 2).min(width - x);
+        let h = (region.height + pad * 2).min(height - y);
+        if w == 0 || h == 0 {
+            continue;
+        }
+
+        let sub = image::imageops::crop_imm(image, x, y, w, h).to_image();
+        let sigma = (h as f32 / 2.0).max(4.0);
+        let soft = image::imageops::blur(&sub, sigma);
+        image::imageops::replace(image, &soft, x as i64, y as i64);
+        blurred += 1;
+    }
+
+    blurred
+}
+
+/// Current effective settings
+pub fn current_settings() -> RedactionSettings {
+    REDACTION_SETTINGS.lock().unwrap().clone()
+}
+
+/// Mask API keys, bearer tokens and emails in log output (see `logging.rs`)
+///
+/// Numbers are left alone: phone and card detectors would eat sizes and coordinates
+pub fn redact_log_text(text: &str) -> Cow<'_, str> {
+    let mut output = Cow::Borrowed(text);
+    for (kind, re) in BUILTIN_PATTERNS.iter() {
+        if matches!(kind, PiiKind::ApiKey | PiiKind::Email) && re.is_match(&output) {
+            output = Cow::Owned(re.replace_all(&output, kind.mask()).into_owned());
+        }
+    }
+    output
+}
+
+/// Mask text for `provider` if redaction is enabled for it
+pub fn redact_text_for(provider: &str, text: &str) -> Result<(String, RedactionReport), String> {
+    redact_text_with(&current_settings(), provider, text)
+}
+
+/// Fails when the redactor cannot be built, the text must then not be sent
+fn redact_text_with(
+    settings: &RedactionSettings,
+    provider: &str,
+    text: &str,
+) -> Result<(String, RedactionReport), String> {
+    let mut report = RedactionReport {
+        provider: provider.to_string(),
+        enabled: settings.is_enabled_for(provider),
+        ..Default::default()
+    };
+
+    if !report.enabled {
+        return Ok((text.to_string(), report));
+    }
+
+    // Settings are validated on write, this only fails with a corrupted state
+    let redactor = Redactor::new(settings).map_err(|e| format!("Redaction unavailable: {}", e))?;
+    let (masked, findings) = redactor.redact_text(text);
+    report.record(&findings);
+    Ok((masked, report))
+}
+
+/// Blur the sensitive regions of an image about to be uploaded to `provider`
+///
+/// Fails when the image can't be scanned (no OCR engine, broken redactor): it must then not be sent
+pub fn redact_image_with(
+    settings: &RedactionSettings,
+    provider: &str,
+    recognizer: Result<&dyn TextRecognizer, String>,
+    image: &mut RgbaImage,
+) -> Result<RedactionReport, String> {
+    let mut report = RedactionReport {
+        provider: provider.to_string(),
+        enabled: settings.is_enabled_for(provider),
+        ..Default::default()
+    };
+
+    if !report.enabled || !settings.blur_images {
+        return Ok(report);
+    }
+
+    let redactor = Redactor::new(settings).map_err(|e| format!("Redaction unavailable: {}", e))?;
+    let ocr = recognizer
+        .and_then(|recognizer| crate::ocr::recognize_image(recognizer, image).map_err(|e| e.to_string()))
+        .map_err(|e| format!("Redaction unavailable: {}", e))?;
+
+    for line in &ocr.lines {
+        report.record(&redactor.find(&line.text));
+    }
+    report.regions_blurred = blur_regions(image, &redactor.sensitive_regions(&ocr));
+
+    debug!("🙈 Image redacted for '{}': {} regions", provider, report.regions_blurred);
+    Ok(report)
+}
+
+/// Capture ready to be uploaded
+#[derive(Serialize, Debug, Clone)]
+pub struct RedactedCapture {
+    pub image_path: String,
+    pub text: String,
+    pub context: Option<String>,
+    pub report: RedactionReport,
+}
+
+/// Redact a capture (image + OCR text) for a provider
+pub fn redact_capture_file(
+    settings: &RedactionSettings,
+    provider: &str,
+    image_path: &Path,
+    ocr: &OcrResult,
+) -> Result<RedactedCapture, String> {
+    let mut report = RedactionReport {
+        provider: provider.to_string(),
+        enabled: settings.is_enabled_for(provider),
+        ..Default::default()
+    };
+
+    if !report.enabled {
+        debug!("🙈 Redaction disabled for provider '{}'", provider);
+        return Ok(RedactedCapture {
+            image_path: image_path.to_string_lossy().to_string(),
+            text: ocr.text(),
+            context: ocr.to_context(crate::ocr::DEFAULT_CONTEXT_CHARS),
+            report,
+        });
+    }
+
+    let redactor = Redactor::new(settings)?;
+
+    // Mask the OCR text line by line so the context keeps its layout
+    let mut masked = ocr.clone();
+    for line in &mut masked.lines {
+        let (text, findings) = redactor.redact_text(&line.text);
+        report.record(&findings);
+        line.text = text;
+        line.words.clear();
+    }
+
+    // Blur the matching regions in a copy of the image
+    let mut output_path = image_path.to_path_buf();
+    if settings.blur_images {
+        let regions = redactor.sensitive_regions(ocr);
+        if !regions.is_empty() {
+            let mut image = image::open(image_path)
+                .map_err(|e| format!("Erreur de lecture: {}", e))?
+                .to_rgba8();
+            report.regions_blurred = blur_regions(&mut image, &regions);
+
+            let stem = image_path.file_stem().and_then(|s| s.to_str()).unwrap_or("capture");
+            output_path = image_path.with_file_name(format!("{}_redacted.png", stem));
+            image.save(&output_path).map_err(|e| e.to_string())?;
+        }
+    }
+
+    info!("🙈 Capture redacted for '{}': {} text matches, {} regions",
+          provider, report.text_replacements, report.regions_blurred);
+
+    Ok(RedactedCapture {
+        image_path: output_path.to_string_lossy().to_string(),
+        text: masked.text(),
+        context: masked.to_context(crate::ocr::DEFAULT_CONTEXT_CHARS),
+        report,
+    })
+}
+
+/// Get redaction settings
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all)]
+pub fn get_redaction_settings() -> RedactionSettings {
+    current_settings()
+}
+
+/// Update redaction settings
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+pub fn set_redaction_settings(settings: RedactionSettings) -> Result<RedactionSettings, String> {
+    validate_input("set_redaction_settings", settings, |validated| {
+        *REDACTION_SETTINGS.lock().unwrap() = validated.clone();
+        info!("🙈 Redaction settings updated ({} custom patterns)", validated.custom_patterns.len());
+        Ok(validated)
+    })
+}
+
+/// Run OCR on a capture and redact image and text for `provider`
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+pub async fn redact_capture(app: AppHandle, image_path: String, provider: String) -> Result<RedactedCapture, String> {
+    let recognizer = app
+        .state::<crate::ocr::OcrState>()
+        .recognizer(&app)
+        .map_err(|e| e.to_string())?;
+    let settings = current_settings();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = Path::new(&image_path);
+        let ocr = crate::ocr::recognize_file(recognizer.as_ref(), path).map_err(|e| e.to_string())?;
+        redact_capture_file(&settings, &provider, path, &ocr)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ocr::{OcrLine, OcrWord};
+
+    fn redactor() -> Redactor {
+        Redactor::new(&RedactionSettings::default()).unwrap()
+    }
+
+    fn fixture(name: &str) -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/ocr").join(name)
+    }
+
+    fn bbox(x: u32, y: u32, width: u32, height: u32) -> BoundingBox {
+        BoundingBox { x, y, width, height }
+    }
+
+    #[test]
+    fn test_builtin_detectors() {
+        let text = "Mail jane.doe@example.com or call +33 6 12 34 56 78, \
+                    card 4111 1111 1111 1111, key sk-abcdefghijklmnop1234";
+        let (masked, findings) = redactor().redact_text(text);
+
+        assert!(!masked.contains("jane.doe"));
+        assert!(!masked.contains("4111"));
+        assert!(!masked.contains("sk-abc"));
+        assert!(!masked.contains("12 34 56"));
+        assert!(masked.contains("[REDACTED:EMAIL]"));
+        assert!(masked.contains("[REDACTED:CARD]"));
+        assert!(masked.contains("[REDACTED:API_KEY]"));
+        assert!(masked.contains("[REDACTED:PHONE]"));
+        assert_eq!(findings.len(), 4);
+    }
+
+    #[test]
+    fn test_no_false_positives_on_plain_text() {
+        let text = "Meeting at 10:30 in room 42, version 1.2.3, order #1234";
+        let (masked, findings) = redactor().redact_text(text);
+        assert_eq!(masked, text);
+        assert!(findings.is_empty());
+
+        // Sixteen digits failing the Luhn check are not a card
+        assert!(redactor().find("4111 1111 1111 1112").iter().all(|f| f.kind != PiiKind::CardNumber));
+
+        // IPv4 addresses, version strings and numeric ids are not phone numbers
+        for text in [
+            "host 192.168.100.200 is up",
+            "gateway 10.0.0.1",
+            "macOS 14.4.1 build 23E224, app 2024.10.19.1234",
+            "order 123456789012",
+            "user id 4815162342",
+            "released 2024-10-19 12:30",
+        ] {
+            assert!(redactor().find(text).is_empty(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_phone_formats() {
+        for phone in ["+33 6 12 34 56 78", "+1 (555) 123-4567", "(555) 123-4567", "06.12.34.56.78", "0612345678", "555-123-4567"] {
+            let findings = redactor().find(&format!("call {} today", phone));
+            assert_eq!(findings.len(), 1, "{}", phone);
+            assert_eq!(findings[0].kind, PiiKind::Phone, "{}", phone);
+        }
+    }
+
+    #[test]
+    fn test_custom_patterns_and_validation() {
+        let settings = RedactionSettings {
+            custom_patterns: vec![CustomPattern { name: "ticket".into(), pattern: r"PROJ-\d+".into() }],
+            ..RedactionSettings::default()
+        };
+        assert!(settings.validate().is_ok());
+
+        let (masked, findings) = Redactor::new(&settings).unwrap().redact_text("see PROJ-1234");
+        assert_eq!(masked, "see [REDACTED]");
+        assert_eq!(findings[0].label, "ticket");
+
+        let invalid = RedactionSettings {
+            custom_patterns: vec![CustomPattern { name: "broken".into(), pattern: "(".into() }],
+            ..RedactionSettings::default()
+        };
+        assert!(invalid.validate().is_err());
+        // Never sent unmasked, nor silently dropped
+        assert!(redact_text_with(&invalid, "openai", "see PROJ-1234").is_err());
+        assert_eq!(redact_text_with(&settings, "openai", "see PROJ-1234").unwrap().0, "see [REDACTED]");
+    }
+
+    #[test]
+    fn test_provider_toggle() {
+        let settings = RedactionSettings::default();
+        assert!(settings.is_enabled_for("openai"));
+        assert!(!settings.is_enabled_for("ollama"));
+        assert!(settings.is_enabled_for("some-new-cloud"));
+    }
+
+    #[test]
+    fn test_redact_capture_blurs_email_region() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("capture.png");
+        std::fs::copy(fixture("invoice.png"), &path).unwrap();
+
+        // Layout of the fixture: title at y=20, email at y=80
+        let ocr = OcrResult {
+            width: 480,
+            height: 140,
+            lines: vec![
+                OcrLine {
+                    text: "Invoice 42".into(),
+                    bbox: bbox(20, 20, 160, 32),
+                    words: vec![
+                        OcrWord { text: "Invoice".into(), bbox: bbox(20, 20, 110, 32) },
+                        OcrWord { text: "42".into(), bbox: bbox(140, 20, 40, 32) },
+                    ],
+                },
+                OcrLine {
+                    text: "jane.doe@example.com".into(),
+                    bbox: bbox(20, 80, 300, 30),
+                    words: vec![OcrWord { text: "jane.doe@example.com".into(), bbox: bbox(20, 80, 300, 30) }],
+                },
+            ],
+        };
+
+        let result = redact_capture_file(&RedactionSettings::default(), "openai", &path, &ocr).unwrap();
+        assert_eq!(result.report.regions_blurred, 1);
+        assert_eq!(result.report.counts.get("email"), Some(&1));
+        assert!(result.text.contains("Invoice 42"));
+        assert!(!result.text.contains("jane.doe"));
+        assert!(result.image_path.ends_with("capture_redacted.png"));
+
+        let original = image::open(&path).unwrap().to_rgba8();
+        let redacted = image::open(&result.image_path).unwrap().to_rgba8();
+
+        // Title untouched, email area changed
+        let title = |img: &RgbaImage| image::imageops::crop_imm(img, 20, 20, 160, 32).to_image();
+        let email = |img: &RgbaImage| image::imageops::crop_imm(img, 20, 80, 300, 30).to_image();
+        assert_eq!(title(&original), title(&redacted));
+        assert_ne!(email(&original), email(&redacted));
+
+        // Local provider: nothing is touched
+        let local = redact_capture_file(&RedactionSettings::default(), "ollama", &path, &ocr).unwrap();
+        assert!(!local.report.enabled);
+        assert!(local.text.contains("jane.doe@example.com"));
+        assert_eq!(local.image_path, path.to_string_lossy());
+    }
+}
@@ -0,0 +1,161 @@
+// src-tauri/src/sse.rs
+//! 📡 Server-sent events parser for streamed chat completions
+//!
+//! Features:
+//! - Incremental: chunks may split lines, fields and UTF-8 sequences anywhere
+//! - LF and CRLF line endings, comments and unknown fields ignored
+//! - Multi-line `data:` fields joined with `\n`
+//! - Bounded buffer, oversized lines are dropped
+//! - `[DONE]` sentinel detection (OpenAI streaming)
+
+use tracing::warn;
+
+/// Longest line kept in the buffer
+const MAX_LINE_BYTES: usize = 1024 * 1024;
+
+/// End of stream marker sent by OpenAI
+pub const DONE: &str = "[DONE]";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseEvent {
+    /// `event:` field, `None` for the default `message` type
+    pub event: Option<String>,
+    pub data: String,
+}
+
+impl SseEvent {
+    pub fn is_done(&self) -> bool {
+        self.data == DONE
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SseParser {
+    /// Bytes of the current, unterminated line
+    line: Vec<u8>,
+    /// Current line exceeded `MAX_LINE_BYTES`, skip until its end
+    discarding: bool,
+    event: Option<String>,
+    data: Vec<String>,
+}
+
+impl SseParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a chunk, returns the events it completes
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        let mut events = Vec::new();
+
+        for part in chunk.split_inclusive(|b| *b == b'\n') {
+            let complete = part.last() == Some(&b'\n');
+            let content = if complete { &part[..part.len() - 1] } else { part };
+
+            if !self.discarding {
+                if self.line.len() + content.len() > MAX_LINE_BYTES {
+                    warn!("📡 SSE line over {} bytes dropped", MAX_LINE_BYTES);
+                    self.line.clear();
+                    self.discarding = true;
+                } else {
+                    self.line.extend_from_slice(content);
+                }
+            }
+
+            if complete {
+                if !self.discarding {
+                    let line = std::mem::take(&mut self.line);
+                    if let Some(event) = self.process_line(&line) {
+                        events.push(event);
+                    }
+                }
+                self.discarding = false;
+            }
+        }
+        events
+    }
+
+    fn process_line(&mut self, line: &[u8]) -> Option<SseEvent> {
+        // `\n` never occurs inside a UTF-8 sequence, the line is complete text
+        let line = String::from_utf8_lossy(line);
+        let line = line.strip_suffix('\r').unwrap_or(&line);
+
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "data" => self.data.push(value.to_string()),
+            "event" => self.event = Some(value.to_string()),
+            _ => {} // id, retry
+        }
+        None
+    }
+
+    /// Blank line: emit the pending event, if it has data
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        let event = self.event.take();
+        if self.data.is_empty() {
+            return None;
+        }
+        Some(SseEvent { event, data: std::mem::take(&mut self.data).join("\n") })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STREAM: &str = "data: {\"choices\":[{\"delta\":{\"content\":\"Bon\"}}]}\n\n\
+                          : keep-alive\n\n\
+                          data: {\"choices\":[{\"delta\":{\"content\":\"jour é\"}}]}\n\n\
+                          data: [DONE]\n\n";
+
+    #[test]
+    fn test_openai_stream() {
+        let events = SseParser::new().push(STREAM.as_bytes());
+        assert_eq!(events.len(), 3);
+        assert!(events[1].data.contains("jour é"));
+        assert!(events[2].is_done());
+    }
+
+    #[test]
+    fn test_any_split_gives_the_same_events() {
+        let bytes = STREAM.as_bytes();
+        let expected = SseParser::new().push(bytes);
+
+        for split in 0..bytes.len() {
+            let mut parser = SseParser::new();
+            let mut events = parser.push(&bytes[..split]);
+            events.extend(parser.push(&bytes[split..]));
+            assert_eq!(events, expected, "split at {}", split);
+        }
+    }
+
+    #[test]
+    fn test_fields_and_line_endings() {
+        let mut parser = SseParser::new();
+        let events = parser.push(b"event: usage\r\nid: 7\r\ndata: line 1\r\ndata:line 2\r\n\r\ndata\n\n");
+        assert_eq!(events[0], SseEvent { event: Some("usage".into()), data: "line 1\nline 2".into() });
+        assert_eq!(events[1].data, "");
+
+        // Unterminated event is not emitted
+        assert!(parser.push(b"data: partial\n").is_empty());
+    }
+
+    #[test]
+    fn test_oversized_line_is_dropped() {
+        let mut parser = SseParser::new();
+        assert!(parser.push(b"data: ").is_empty());
+        assert!(parser.push(&vec![b'a'; MAX_LINE_BYTES]).is_empty());
+        let events = parser.push(b"\n\ndata: ok\n\n");
+        assert_eq!(events, vec![SseEvent { event: None, data: "ok".into() }]);
+    }
+}
@@ -15,6 +15,12 @@ impl StealthState {
     pub fn is_active(&self) -> bool {
         *self.0.lock().unwrap()
     }
+
+    /// État initial choisi, pour les apps de test
+    #[cfg(test)]
+    pub fn with_active(active: bool) -> Self {
+        Self(Arc::new(Mutex::new(active)))
+    }
 }
 
 /// Initialiser dans `tauri::Builder`
@@ -27,6 +33,13 @@ pub fn toggle_stealth(app: &AppHandle) -> Result<(), String> {
         *guard
     };
 
+    // 🔭 Pas d'export OTLP en mode furtif
+    #[cfg(feature = "otel")]
+    crate::otel::set_privacy_mode(active);
+
+    // 🛡️ CSP des fenêtres ouvertes : télémétrie bloquée en mode furtif
+    crate::csp_manager::on_stealth_changed(app, active);
+
     #[cfg(all(target_os = "macos", feature = "stealth_macos"))]
     {
         info!("🕵️ Toggle stealth: active = {}", active);
@@ -110,6 +123,10 @@ pub fn force_stealth_on(app: &AppHandle) -> Result<(), String> {
         let mut guard = state.0.lock().unwrap();
         *guard = true;
     }
+
+    #[cfg(feature = "otel")]
+    crate::otel::set_privacy_mode(true);
+    crate::csp_manager::on_stealth_changed(app, true);
     
     #[cfg(all(target_os = "macos", feature = "stealth_macos"))]
     {
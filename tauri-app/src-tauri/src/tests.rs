@@ -2,8 +2,11 @@
 mod tests {
     use crate::{
         PANEL_WIDTH, PANEL_HEIGHT, PANEL_INITIAL_X, PANEL_INITIAL_Y,
-        capture_screen_internal, capture_and_analyze, get_image_as_base64
+        capture_screen_internal, image_as_base64
     };
+    use crate::ocr::{BoundingBox, OcrError, OcrLine, OcrWord, TextRecognizer};
+    use crate::redaction::RedactionSettings;
+    use image::RgbaImage;
     use tempfile::tempdir;
 
     // Tests pour les fonctions utilitaires
@@ -20,7 +23,8 @@ mod tests {
     fn test_capture_screen_internal_returns_path() {
         let result = capture_screen_internal();
         match result {
-            Ok(path) => {
+            Ok(capture) => {
+                let path = capture.path.to_string_lossy();
                 assert!(!path.is_empty());
                 assert!(path.contains("screenshot_"));
                 assert!(path.ends_with(".png"));
@@ -32,17 +36,49 @@ mod tests {
         }
     }
 
-    #[test]
-    fn test_capture_and_analyze_returns_string() {
-        let result = capture_and_analyze();
-        assert!(!result.is_empty());
-        assert!(result.contains("Capture") || result.contains("Erreur"));
+    // Tests pour la conversion base64
+    fn no_ocr() -> Result<&'static dyn TextRecognizer, String> {
+        Err("OCR indisponible".to_string())
+    }
+
+    fn without_redaction() -> RedactionSettings {
+        let mut settings = RedactionSettings::default();
+        settings.providers.insert("openai".to_string(), false);
+        settings
+    }
+
+    /// Reconnaît l'email de la fixture invoice.png (titre à y=20, email à y=80)
+    struct InvoiceRecognizer;
+
+    impl TextRecognizer for InvoiceRecognizer {
+        fn recognize(&self, _image: &RgbaImage) -> Result<Vec<OcrLine>, OcrError> {
+            let bbox = BoundingBox { x: 20, y: 80, width: 300, height: 30 };
+            Ok(vec![OcrLine {
+                text: "jane.doe@example.com".into(),
+                bbox,
+                words: vec![OcrWord { text: "jane.doe@example.com".into(), bbox }],
+            }])
+        }
+    }
+
+    fn decode_payload(data_url: &str) -> RgbaImage {
+        use base64::{Engine as _, engine::general_purpose};
+        let (_, payload) = data_url.split_once(";base64,").unwrap();
+        let bytes = general_purpose::STANDARD.decode(payload).unwrap();
+        image::load_from_memory(&bytes).unwrap().to_rgba8()
+    }
+
+    /// Écart moyen par canal entre deux zones de même taille
+    fn mean_difference(a: &RgbaImage, b: &RgbaImage, (x, y, w, h): (u32, u32, u32, u32)) -> f64 {
+        let a = image::imageops::crop_imm(a, x, y, w, h).to_image();
+        let b = image::imageops::crop_imm(b, x, y, w, h).to_image();
+        let total: u64 = a.as_raw().iter().zip(b.as_raw()).map(|(p, q)| p.abs_diff(*q) as u64).sum();
+        total as f64 / a.as_raw().len() as f64
     }
 
-    // Tests pour la conversion base64
     #[test]
     fn test_get_image_as_base64_with_invalid_path() {
-        let result = get_image_as_base64("invalid_path.png".to_string());
+        let result = image_as_base64("invalid_path.png", &RedactionSettings::default(), no_ocr());
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("Erreur de lecture"));
     }
@@ -53,12 +89,36 @@ mod tests {
         let temp_dir = tempdir().unwrap();
         let png_path = temp_dir.path().join("test.png");
         std::fs::write(&png_path, b"fake png data").unwrap();
-        
-        let result = get_image_as_base64(png_path.to_string_lossy().to_string());
-        if result.is_ok() {
-            let base64_string = result.unwrap();
-            assert!(base64_string.starts_with("data:image/png;base64,"));
-        }
+        let path = png_path.to_string_lossy().to_string();
+
+        // Sans redaction, le fichier illisible part tel quel
+        let base64_string = image_as_base64(&path, &without_redaction(), no_ocr()).unwrap();
+        assert!(base64_string.starts_with("data:image/png;base64,"));
+
+        // Avec redaction, une image qui ne peut pas être analysée n'est jamais envoyée
+        assert!(image_as_base64(&path, &RedactionSettings::default(), no_ocr()).is_err());
+    }
+
+    #[test]
+    fn test_upload_payload_has_sensitive_regions_blurred() {
+        let fixture = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/ocr/invoice.png");
+        let path = fixture.to_string_lossy().to_string();
+        let original = image::open(&fixture).unwrap().to_rgba8();
+        let title = (20, 20, 160, 32);
+        let email = (20, 80, 300, 30);
+
+        let redacted = decode_payload(&image_as_base64(&path, &RedactionSettings::default(), Ok(&InvoiceRecognizer)).unwrap());
+        assert_eq!(redacted.dimensions(), original.dimensions());
+        // Titre intact (au bruit JPEG près), email flouté
+        assert!(mean_difference(&original, &redacted, title) < 4.0);
+        assert!(mean_difference(&original, &redacted, email) > 10.0);
+
+        // Sans redaction pour le fournisseur, l'email reste lisible
+        let plain = decode_payload(&image_as_base64(&path, &without_redaction(), Ok(&InvoiceRecognizer)).unwrap());
+        assert!(mean_difference(&original, &plain, email) < 4.0);
+
+        // Sans OCR, rien n'est envoyé
+        assert!(image_as_base64(&path, &RedactionSettings::default(), no_ocr()).unwrap_err().contains("Redaction unavailable"));
     }
 
     // Tests pour les fonctions de fenêtre
@@ -180,4 +240,87 @@ mod tests {
             assert!(name.len() < 50); // Nom raisonnable
         }
     }
+
+    // Tests de la CSP appliquée aux fenêtres
+    #[test]
+    fn test_window_csp_follows_consent_and_stealth() {
+        use crate::csp_manager::{initialization_script, telemetry_allowed};
+
+        // (consentement, mode furtif) -> télémétrie autorisée
+        let cases = [
+            (false, false, false),
+            (false, true, false),
+            (true, true, false),
+            (true, false, true),
+        ];
+        for (consent, stealth, expected) in cases {
+            assert_eq!(telemetry_allowed(consent, stealth), expected);
+
+            let script = initialization_script(telemetry_allowed(consent, stealth));
+            assert_eq!(script.contains("sentry.io"), expected, "{}", script);
+            // report-uri n'est pas accepté dans une balise <meta>
+            assert!(!script.contains("report-uri"));
+        }
+    }
+
+    #[test]
+    fn test_config_windows_are_built_with_csp() {
+        // Les fenêtres de tauri.conf.json chargent une URL externe : la CSP statique ne s'y applique pas,
+        // elles doivent être construites dans setup via csp_manager::with_csp
+        let config: serde_json::Value = serde_json::from_str(include_str!("../tauri.conf.json")).unwrap();
+        let windows = config["app"]["windows"].as_array().unwrap();
+        assert!(!windows.is_empty());
+
+        for window in windows {
+            assert_eq!(window["create"], false, "fenêtre créée sans CSP : {}", window["label"]);
+        }
+    }
+
+    #[test]
+    fn test_built_windows_enforce_dynamic_policy() {
+        use crate::consent::{self, Consent};
+        use crate::csp_manager::{enforced_policy, get_dynamic_csp_policy, meta_policy};
+        use crate::stealth::StealthState;
+        use crate::{draggable_window_builder, hud_builder, panel_builder, DraggableWindowConfig};
+        use tauri::test::{mock_builder, mock_context, noop_assets};
+
+        let _serial = consent::TEST_SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        let config: serde_json::Value = serde_json::from_str(include_str!("../tauri.conf.json")).unwrap();
+        let hud: tauri::utils::config::WindowConfig = config["app"]["windows"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|w| w["label"] == "hud")
+            .map(|w| serde_json::from_value(w.clone()).unwrap())
+            .unwrap();
+
+        let previous = consent::current();
+        for (error_reporting, stealth) in [(false, false), (false, true), (true, true), (true, false)] {
+            consent::set_current_for_test(Consent { error_reporting, ..Consent::default() });
+
+            // Une app neuve par état : les fenêtres passent par les mêmes builders que l'app
+            let mut context = mock_context(noop_assets());
+            context.config_mut().app.windows = vec![hud.clone()];
+            let app = mock_builder()
+                .manage(StealthState::with_active(stealth))
+                .build(context)
+                .unwrap();
+            let handle = app.handle();
+
+            hud_builder(handle).unwrap().expect("fenêtre hud dans tauri.conf.json").build().unwrap();
+            panel_builder(handle).build().unwrap();
+            draggable_window_builder(handle, "input", &DraggableWindowConfig::for_input()).build().unwrap();
+            draggable_window_builder(handle, "context", &DraggableWindowConfig::for_context()).build().unwrap();
+
+            let expected = meta_policy(&get_dynamic_csp_policy(handle.clone()));
+            for label in ["hud", "panel", "input", "context"] {
+                assert_eq!(
+                    enforced_policy(label).as_deref(),
+                    Some(expected.as_str()),
+                    "{} (consentement={}, furtif={})", label, error_reporting, stealth
+                );
+            }
+        }
+        consent::set_current_for_test(previous);
+    }
 }
@@ -0,0 +1,248 @@
+// src-tauri/src/window_capture.rs
+//! 🪟 Single-window capture for Numa
+//!
+//! Features:
+//! - Window enumeration (title, app name, bounds)
+//! - Capture of one chosen window instead of the whole desktop
+//! - Numa's own windows (hud, input, context, panel) are never listed
+//! - Explicit error on platforms without window capture support
+
+use image::RgbaImage;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use thiserror::Error;
+use tracing::{info, debug};
+use crate::history::{self, CaptureRecord, CapturedWindow, NewCapture};
+
+/// Numa windows that must never appear in the list
+const EXCLUDED_LABELS: &[&str] = &["hud", "input", "context", "panel"];
+
+#[derive(Error, Debug)]
+pub enum WindowCaptureError {
+    #[error("Window capture is not supported on {0}")]
+    Unsupported(&'static str),
+
+    #[error("Window {0} not found")]
+    NotFound(u32),
+
+    #[error("Window {0} belongs to Numa and cannot be captured")]
+    Excluded(u32),
+
+    #[error("Window capture failed: {0}")]
+    Backend(String),
+}
+
+/// Capturable window
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct WindowInfo {
+    pub id: u32,
+    pub pid: u32,
+    pub title: String,
+    pub app_name: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub is_minimized: bool,
+}
+
+/// Identifies Numa's own windows
+#[derive(Debug, Clone, Default)]
+pub struct OwnWindows {
+    pub pid: u32,
+    pub titles: Vec<String>,
+}
+
+impl OwnWindows {
+    /// Current process and titles of the excluded webview windows
+    pub fn from_app(app: &AppHandle) -> Self {
+        let titles = EXCLUDED_LABELS
+            .iter()
+            .filter_map(|label| app.get_webview_window(label))
+            .filter_map(|window| window.title().ok())
+            .filter(|title| !title.is_empty())
+            .collect();
+
+        Self { pid: std::process::id(), titles }
+    }
+
+    pub fn contains(&self, window: &WindowInfo) -> bool {
+        window.pid == self.pid || self.titles.iter().any(|title| title == &window.title)
+    }
+}
+
+/// Keep windows the user can meaningfully capture
+pub fn filter_windows(windows: Vec<WindowInfo>, own: &OwnWindows) -> Vec<WindowInfo> {
+    windows
+        .into_iter()
+        .filter(|w| !own.contains(w))
+        .filter(|w| !w.is_minimized && w.width > 0 && w.height > 0)
+        .filter(|w| !w.title.trim().is_empty() || !w.app_name.trim().is_empty())
+        .collect()
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+mod backend {
+    use super::{WindowCaptureError, WindowInfo};
+    use image::RgbaImage;
+
+    fn backend_error(e: xcap::XCapError) -> WindowCaptureError {
+        WindowCaptureError::Backend(e.to_string())
+    }
+
+    fn info(window: &xcap::Window) -> Result<WindowInfo, xcap::XCapError> {
+        Ok(WindowInfo {
+            id: window.id()?,
+            pid: window.pid()?,
+            title: window.title()?,
+            app_name: window.app_name()?,
+            x: window.x()?,
+            y: window.y()?,
+            width: window.width()?,
+            height: window.height()?,
+            is_minimized: window.is_minimized()?,
+        })
+    }
+
+    pub fn list() -> Result<Vec<WindowInfo>, WindowCaptureError> {
+        let windows = xcap::Window::all().map_err(backend_error)?;
+        // Windows closing during enumeration are skipped
+        Ok(windows.iter().filter_map(|w| info(w).ok()).collect())
+    }
+
+    pub fn capture(id: u32) -> Result<(WindowInfo, RgbaImage), WindowCaptureError> {
+        let windows = xcap::Window::all().map_err(backend_error)?;
+        let window = windows
+            .iter()
+            .find(|w| w.id().ok() == Some(id))
+            .ok_or(WindowCaptureError::NotFound(id))?;
+
+        let info = info(window).map_err(backend_error)?;
+        let captured = window.capture_image().map_err(|e| match e {
+            xcap::XCapError::NotSupported => WindowCaptureError::Unsupported(std::env::consts::OS),
+            other => backend_error(other),
+        })?;
+
+        // xcap uses its own `image` version, convert through the raw buffer
+        let (width, height) = captured.dimensions();
+        let image = RgbaImage::from_raw(width, height, captured.into_raw())
+            .ok_or_else(|| WindowCaptureError::Backend("invalid image buffer".to_string()))?;
+
+        Ok((info, image))
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod backend {
+    use super::{WindowCaptureError, WindowInfo};
+    use image::RgbaImage;
+
+    pub fn list() -> Result<Vec<WindowInfo>, WindowCaptureError> {
+        Err(WindowCaptureError::Unsupported(std::env::consts::OS))
+    }
+
+    pub fn capture(_id: u32) -> Result<(WindowInfo, RgbaImage), WindowCaptureError> {
+        Err(WindowCaptureError::Unsupported(std::env::consts::OS))
+    }
+}
+
+/// Capture one window, refusing Numa's own windows
+pub fn capture_window_image(id: u32, own: &OwnWindows) -> Result<(WindowInfo, RgbaImage), WindowCaptureError> {
+    let (info, image) = backend::capture(id)?;
+    if own.contains(&info) {
+        return Err(WindowCaptureError::Excluded(id));
+    }
+    Ok((info, image))
+}
+
+/// List windows that can be captured
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+pub fn list_windows(app: AppHandle) -> Result<Vec<WindowInfo>, String> {
+    let own = OwnWindows::from_app(&app);
+    let windows = filter_windows(backend::list().map_err(|e| e.to_string())?, &own);
+    debug!("🪟 {} capturable windows", windows.len());
+    Ok(windows)
+}
+
+/// Capture a single window and add it to the history
+#[tauri::command]
+#[tracing::instrument(target = "ipc", skip_all, err(level = "warn"))]
+pub async fn capture_window(app: AppHandle, window_id: u32) -> Result<CaptureRecord, String> {
+    let own = OwnWindows::from_app(&app);
+
+    let (info, image) = tauri::async_runtime::spawn_blocking(move || capture_window_image(window_id, &own))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+
+    let encoded = crate::capture::encode(&image, &crate::capture::current_settings().capture)?;
+    let path = crate::capture::save_to_temp(&encoded, "window")?;
+
+    info!("🪟 Window captured: '{}' ({})", info.title, info.app_name);
+
+    history::with_history(&app, |index| {
+        index.import(NewCapture {
+            path,
+            display_id: 0,
+            width: encoded.width,
+            height: encoded.height,
+            format: encoded.format,
+            window: Some(CapturedWindow { title: info.title, app_name: info.app_name }),
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(id: u32, pid: u32, title: &str) -> WindowInfo {
+        WindowInfo {
+            id,
+            pid,
+            title: title.to_string(),
+            app_name: "App".to_string(),
+            x: 0,
+            y: 0,
+            width: 800,
+            height: 600,
+            is_minimized: false,
+        }
+    }
+
+    #[test]
+    fn test_own_windows_are_excluded() {
+        let own = OwnWindows { pid: 42, titles: vec!["Numa HUD".to_string()] };
+        let windows = vec![
+            window(1, 42, "input"),
+            window(2, 7, "Numa HUD"),
+            window(3, 7, "main.rs - Code"),
+        ];
+
+        let listed = filter_windows(windows, &own);
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, 3);
+    }
+
+    #[test]
+    fn test_minimized_and_empty_windows_are_skipped() {
+        let own = OwnWindows::default();
+        let mut minimized = window(1, 7, "Mail");
+        minimized.is_minimized = true;
+        let mut empty = window(2, 7, "Tooltip");
+        empty.width = 0;
+        let mut untitled = window(3, 7, "");
+        untitled.app_name = String::new();
+
+        assert!(filter_windows(vec![minimized, empty, untitled], &own).is_empty());
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    #[test]
+    fn test_unsupported_platform_error_is_explicit() {
+        let err = backend::list().unwrap_err();
+        assert!(err.to_string().contains("not supported"));
+        assert!(capture_window_image(1, &OwnWindows::default()).is_err());
+    }
+}
@@ -0,0 +1,3 @@
+fn parse(input: &str) -> Option<u32> {
+    input.trim().parse().ok()
+}
@@ -0,0 +1,341 @@
+//! 🛡️ `#[derive(ValidatedInput)]` for Numa IPC inputs
+//!
+//! Generates `ValidatedInput::validate()` (first failing rule) and
+//! `ValidatedInput::validation_errors()` (every failing rule) from
+//! field attributes:
+//!
+//! ```ignore
+//! #[derive(ValidatedInput)]
+//! pub struct SecureKey {
+//!     #[validate(non_empty, len(max = 64), charset = "alnum_-.")]
+//!     pub key: String,
+//! }
+//! ```
+//!
+//! Rules, checked in the order they are written:
+//! - `non_empty`: string is not empty after trimming
+//! - `len(min = .., max = ..)`: byte length bounds
+//! - `charset = "alnum_-."`: ASCII alphanumerics (`alnum` prefix) plus the listed characters,
+//!   so identifiers cannot carry look-alike Unicode letters
+//! - `range(a..=b)`: numeric bounds, inclusive
+//! - `custom = "path::to::fn"`: `fn(field: &str, value: &T) -> Result<(), ValidationError>`
+//! - `nested`: field implements `ValidatedInput`
+//!
+//! `Option<T>` fields are only checked when they are `Some`.
+//! Generated code refers to `crate::validation::{ValidatedInput, ValidationError}`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::{parse_macro_input, Data, DeriveInput, Expr, ExprRange, Fields, Lit, Meta, Token, Type};
+
+#[proc_macro_derive(ValidatedInput, attributes(validate))]
+pub fn derive_validated_input(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(|e| e.to_compile_error()).into()
+}
+
+/// One validation rule on a field
+enum Rule {
+    NonEmpty,
+    Len { min: Option<Expr>, max: Option<Expr> },
+    Charset { alnum: bool, extra: String },
+    Range { min: Expr, max: Expr },
+    Custom(syn::Path),
+    Nested,
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(syn::Error::new_spanned(name, "ValidatedInput requires named fields")),
+        },
+        _ => return Err(syn::Error::new_spanned(name, "ValidatedInput can only be derived for structs")),
+    };
+
+    let mut checks = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named field");
+        let field_name = ident.to_string();
+
+        let mut rules = Vec::new();
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("validate")) {
+            rules.extend(parse_rules(attr)?);
+        }
+        if rules.is_empty() {
+            continue;
+        }
+
+        let rule_checks: Vec<TokenStream2> = rules.iter().map(|rule| rule_check(rule, &field_name)).collect();
+
+        if is_option(&field.ty) {
+            checks.push(quote! {
+                if let Some(value) = &self.#ident {
+                    #(#rule_checks)*
+                }
+            });
+        } else {
+            checks.push(quote! {
+                {
+                    let value = &self.#ident;
+                    #(#rule_checks)*
+                }
+            });
+        }
+    }
+
+    Ok(quote! {
+        impl #impl_generics crate::validation::ValidatedInput for #name #ty_generics #where_clause {
+            fn validate(&self) -> Result<(), crate::validation::ValidationError> {
+                match self.validation_errors().into_iter().next() {
+                    Some(error) => Err(error),
+                    None => Ok(()),
+                }
+            }
+
+            fn validation_errors(&self) -> Vec<crate::validation::ValidationError> {
+                let mut errors: Vec<crate::validation::ValidationError> = Vec::new();
+                #(#checks)*
+                errors
+            }
+        }
+    })
+}
+
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
+
+fn parse_rules(attr: &syn::Attribute) -> syn::Result<Vec<Rule>> {
+    let metas = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+    metas.iter().map(parse_rule).collect()
+}
+
+fn parse_rule(meta: &Meta) -> syn::Result<Rule> {
+    let ident = meta
+        .path()
+        .get_ident()
+        .map(|i| i.to_string())
+        .unwrap_or_default();
+
+    match (ident.as_str(), meta) {
+        ("non_empty", Meta::Path(_)) => Ok(Rule::NonEmpty),
+        ("nested", Meta::Path(_)) => Ok(Rule::Nested),
+        ("len", Meta::List(list)) => {
+            let (mut min, mut max) = (None, None);
+            let args = Punctuated::<syn::MetaNameValue, Token![,]>::parse_terminated.parse2(list.tokens.clone())?;
+            for arg in args {
+                if arg.path.is_ident("min") {
+                    min = Some(arg.value);
+                } else if arg.path.is_ident("max") {
+                    max = Some(arg.value);
+                } else {
+                    return Err(syn::Error::new_spanned(arg.path, "expected `min` or `max`"));
+                }
+            }
+            if min.is_none() && max.is_none() {
+                return Err(syn::Error::new_spanned(list, "len needs `min` and/or `max`"));
+            }
+            Ok(Rule::Len { min, max })
+        }
+        ("range", Meta::List(list)) => {
+            let range: ExprRange = syn::parse2(list.tokens.clone())?;
+            if !matches!(range.limits, syn::RangeLimits::Closed(_)) {
+                return Err(syn::Error::new_spanned(&range, "range must be inclusive (`a..=b`)"));
+            }
+            match (range.start, range.end) {
+                (Some(min), Some(max)) => Ok(Rule::Range { min: *min, max: *max }),
+                _ => Err(syn::Error::new_spanned(list, "range needs both bounds")),
+            }
+        }
+        ("charset", Meta::NameValue(nv)) => {
+            let charset = lit_str(&nv.value)?;
+            match charset.strip_prefix("alnum") {
+                Some(extra) => Ok(Rule::Charset { alnum: true, extra: extra.to_string() }),
+                None => Ok(Rule::Charset { alnum: false, extra: charset }),
+            }
+        }
+        ("custom", Meta::NameValue(nv)) => {
+            let path: syn::Path = syn::parse_str(&lit_str(&nv.value)?)?;
+            Ok(Rule::Custom(path))
+        }
+        _ => Err(syn::Error::new_spanned(
+            meta,
+            "unknown rule, expected one of: non_empty, len(..), charset = \"..\", range(..), custom = \"..\", nested",
+        )),
+    }
+}
+
+fn lit_str(expr: &Expr) -> syn::Result<String> {
+    match expr {
+        Expr::Lit(syn::ExprLit { lit: Lit::Str(s), .. }) => Ok(s.value()),
+        _ => Err(syn::Error::new_spanned(expr, "expected a string literal")),
+    }
+}
+
+/// Code pushing into `errors` when `value` breaks the rule
+fn rule_check(rule: &Rule, field: &str) -> TokenStream2 {
+    let error = quote!(crate::validation::ValidationError);
+
+    match rule {
+        Rule::NonEmpty => quote! {
+            if value.trim().is_empty() {
+                errors.push(#error::EmptyField { field: #field.to_string() });
+            }
+        },
+        Rule::Len { min, max } => {
+            let min_check = min.as_ref().map(|min| quote! {
+                if value.len() < (#min) as usize {
+                    errors.push(#error::InputTooSmall { field: #field.to_string(), min_size: (#min) as usize });
+                }
+            });
+            let max_check = max.as_ref().map(|max| quote! {
+                if value.len() > (#max) as usize {
+                    errors.push(#error::InputTooLarge { field: #field.to_string(), max_size: (#max) as usize });
+                }
+            });
+            quote!(#min_check #max_check)
+        }
+        Rule::Charset { alnum, extra } => {
+            let allowed = if *alnum {
+                quote!(c.is_ascii_alphanumeric() || #extra.contains(c))
+            } else {
+                quote!(#extra.contains(c))
+            };
+            quote! {
+                if !value.chars().all(|c| #allowed) {
+                    errors.push(#error::InvalidCharacters { field: #field.to_string() });
+                }
+            }
+        }
+        Rule::Range { min, max } => quote! {
+            if !((#min) as f64..=(#max) as f64).contains(&(*value as f64)) {
+                errors.push(#error::InvalidRange {
+                    field: #field.to_string(),
+                    min: (#min) as f64,
+                    max: (#max) as f64,
+                });
+            }
+        },
+        Rule::Custom(path) => quote! {
+            if let Err(e) = #path(#field, value) {
+                errors.push(e);
+            }
+        },
+        Rule::Nested => quote! {
+            errors.extend(crate::validation::ValidatedInput::validation_errors(value));
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    /// Generated code without whitespace, easier to search
+    fn expanded(input: DeriveInput) -> String {
+        expand(input).unwrap().to_string().split_whitespace().collect()
+    }
+
+    fn error(input: DeriveInput) -> String {
+        expand(input).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_each_rule_expands_to_its_check() {
+        let code = expanded(parse_quote! {
+            struct Input {
+                #[validate(non_empty, len(min = 2, max = MAX), charset = "alnum_-")]
+                name: String,
+                #[validate(range(1..=100))]
+                quality: u8,
+                #[validate(custom = "crate::validation::key_name")]
+                key: String,
+                #[validate(nested)]
+                options: Options,
+                #[validate(len(max = 8))]
+                tag: Option<String>,
+                unchecked: String,
+            }
+        });
+
+        assert!(code.contains("implcrate::validation::ValidatedInputforInput"));
+        assert!(code.contains("value.trim().is_empty()"));
+        assert!(code.contains("value.len()<(2)asusize") && code.contains("value.len()>(MAX)asusize"));
+        assert!(code.contains("c.is_ascii_alphanumeric()||\"_-\".contains(c)"));
+        assert!(code.contains("((1)asf64..=(100)asf64).contains(&(*valueasf64))"));
+        assert!(code.contains("crate::validation::key_name(\"key\",value)"));
+        assert!(code.contains("crate::validation::ValidatedInput::validation_errors(value)"));
+        assert!(code.contains("ifletSome(value)=&self.tag"));
+        assert!(!code.contains("self.unchecked"));
+    }
+
+    #[test]
+    fn test_charset_without_alnum_only_allows_listed_characters() {
+        let code = expanded(parse_quote! {
+            struct Input {
+                #[validate(charset = "abc")]
+                letters: String,
+            }
+        });
+        assert!(code.contains("value.chars().all(|c|\"abc\".contains(c))"));
+        assert!(!code.contains("alphanumeric"));
+    }
+
+    #[test]
+    fn test_rules_keep_their_written_order() {
+        let code = expanded(parse_quote! {
+            struct Input {
+                #[validate(charset = "alnum", non_empty)]
+                name: String,
+            }
+        });
+        assert!(code.find("InvalidCharacters").unwrap() < code.find("EmptyField").unwrap());
+    }
+
+    #[test]
+    fn test_bad_attributes_are_compile_errors() {
+        let cases: Vec<(DeriveInput, &str)> = vec![
+            (parse_quote! { struct A { #[validate(trimmed)] a: String } }, "unknown rule"),
+            (parse_quote! { struct A { #[validate(non_empty = "yes")] a: String } }, "unknown rule"),
+            (parse_quote! { struct A { #[validate(len())] a: String } }, "len needs `min` and/or `max`"),
+            (parse_quote! { struct A { #[validate(len(size = 3))] a: String } }, "expected `min` or `max`"),
+            (parse_quote! { struct A { #[validate(range(1..10))] a: u8 } }, "range must be inclusive"),
+            (parse_quote! { struct A { #[validate(range(..=10))] a: u8 } }, "range needs both bounds"),
+            (parse_quote! { struct A { #[validate(charset = alnum)] a: String } }, "expected a string literal"),
+            (parse_quote! { struct A { #[validate(custom = check)] a: String } }, "expected a string literal"),
+            (parse_quote! { struct A(#[validate(non_empty)] String); }, "requires named fields"),
+            (parse_quote! { enum A { B } }, "can only be derived for structs"),
+        ];
+
+        for (input, expected) in cases {
+            let message = error(input);
+            assert!(message.contains(expected), "{:?} does not contain {:?}", message, expected);
+        }
+    }
+
+    #[test]
+    fn test_custom_needs_a_valid_path() {
+        let message = error(parse_quote! {
+            struct A {
+                #[validate(custom = "not a path")]
+                a: String,
+            }
+        });
+        assert!(!message.is_empty());
+    }
+}
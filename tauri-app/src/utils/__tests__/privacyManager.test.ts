@@ -0,0 +1,95 @@
+import { describe, it, expect, vi, beforeEach, afterEach } from 'vitest';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
+import { PrivacyManager } from '../privacyManager';
+
+vi.mock('@tauri-apps/api/webviewWindow', () => ({
+  getCurrentWebviewWindow: vi.fn(),
+}));
+
+const GIVEN = {
+  errorReporting: true,
+  logging: false,
+  metrics: true,
+  analytics: false,
+  marketing: false,
+};
+
+// Fenêtre courante, consentement en cache et côté Rust
+function setup(label: string, cached: typeof GIVEN | null, backend: { consent: typeof GIVEN; updatedAt: string | null }) {
+  (getCurrentWebviewWindow as any).mockReturnValue({ label });
+  (localStorage.getItem as any).mockReturnValue(
+    cached && JSON.stringify({ doNotTrack: false, consent: cached, lastUpdated: '', version: '1.0.0' })
+  );
+  (listen as any).mockResolvedValue(() => {});
+  (invoke as any).mockImplementation((command: string) =>
+    Promise.resolve(command === 'get_consent' ? backend : undefined)
+  );
+}
+
+// Nouvelle instance, comme au chargement d'une fenêtre
+async function loadManager() {
+  (PrivacyManager as any).instance = undefined;
+  return PrivacyManager.getInstance();
+}
+
+function setDoNotTrack(value: string | null) {
+  Object.defineProperty(navigator, 'doNotTrack', { value, configurable: true });
+}
+
+describe('PrivacyManager', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+    setDoNotTrack(null);
+  });
+
+  afterEach(() => {
+    setDoNotTrack(null);
+  });
+
+  describe('Do Not Track', () => {
+    it('revokes a consent given earlier in the backend', async () => {
+      setDoNotTrack('1');
+      setup('hud', GIVEN, { consent: GIVEN, updatedAt: '2026-10-01T00:00:00Z' });
+
+      const manager = await loadManager();
+
+      await vi.waitFor(() => expect(invoke).toHaveBeenCalledWith('revoke_consent'));
+      expect(invoke).not.toHaveBeenCalledWith('set_consent', expect.anything());
+      expect(manager.hasConsent('errorReporting')).toBe(false);
+    });
+
+    it('revokes when detected after startup', async () => {
+      vi.useFakeTimers();
+      try {
+        setup('input', GIVEN, { consent: GIVEN, updatedAt: '2026-10-01T00:00:00Z' });
+        const manager = await loadManager();
+        manager.startDoNotTrackListener();
+        expect(invoke).not.toHaveBeenCalledWith('revoke_consent');
+
+        setDoNotTrack('1');
+        vi.advanceTimersByTime(30000);
+
+        expect(invoke).toHaveBeenCalledWith('revoke_consent');
+        expect(manager.getConsent()).toEqual({ ...GIVEN, errorReporting: false, metrics: false });
+      } finally {
+        vi.useRealTimers();
+      }
+    });
+  });
+
+  describe('migration', () => {
+    it('is pushed by the settings panel only', async () => {
+      setup('hud', GIVEN, { consent: { ...GIVEN, errorReporting: false, metrics: false }, updatedAt: null });
+      await loadManager();
+      await vi.waitFor(() => expect(listen).toHaveBeenCalled());
+      expect(invoke).not.toHaveBeenCalledWith('set_consent', expect.anything());
+
+      vi.clearAllMocks();
+      setup('panel', GIVEN, { consent: { ...GIVEN, errorReporting: false, metrics: false }, updatedAt: null });
+      await loadManager();
+      await vi.waitFor(() => expect(invoke).toHaveBeenCalledWith('set_consent', { consent: GIVEN }));
+    });
+  });
+});
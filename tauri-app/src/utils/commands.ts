@@ -65,7 +65,14 @@ export const CommandResults = {
   [COMMAND_NAMES.CAPTURE_SCREEN]: z
     .string()
     .min(1, 'Le chemin de capture est requis'),
-  [COMMAND_NAMES.CAPTURE_AND_ANALYZE]: z.string(),
+  [COMMAND_NAMES.CAPTURE_AND_ANALYZE]: z.object({
+    capture_id: z.string().nullable(),
+    image_path: z.string().min(1, 'Le chemin de capture est requis'),
+    text: z.string(),
+    context: z.string().nullable(),
+    ocr: z.unknown().nullable(),
+    ocr_error: z.string().nullable(),
+  }),
   [COMMAND_NAMES.TOGGLE_STEALTH]: z.void(),
   [COMMAND_NAMES.TEST_STEALTH_MANUAL]: z.void(),
   [COMMAND_NAMES.CLOSE_ALL_WINDOWS]: z.void(),
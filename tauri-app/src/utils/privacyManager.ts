@@ -1,4 +1,8 @@
 // Gestionnaire de confidentialité avec respect de Do Not Track
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
+
 export interface PrivacyConsent {
   errorReporting: boolean;
   logging: boolean;
@@ -14,15 +18,96 @@ export interface PrivacySettings {
   version: string;
 }
 
+// Consentement stocké côté Rust (`consent.rs`), source de vérité
+interface ConsentRecord {
+  consent: PrivacyConsent;
+  updatedAt: string | null;
+}
+
+// Seule fenêtre autorisée à appeler `set_consent` (permissions.rs)
+const SETTINGS_WINDOW = 'panel';
+
+const NO_CONSENT: PrivacyConsent = {
+  errorReporting: false,
+  logging: false,
+  metrics: false,
+  analytics: false,
+  marketing: false,
+};
+
 export class PrivacyManager {
   private static instance: PrivacyManager;
   private settings: PrivacySettings;
   private readonly STORAGE_KEY = 'numa_privacy_settings';
   private readonly VERSION = '1.0.0';
+  private revoking: Promise<void> | null = null;
 
   private constructor() {
     this.settings = this.loadSettings();
     this.checkDoNotTrack();
+    void this.syncWithBackend();
+  }
+
+  // Aligner le cache localStorage sur le backend, puis suivre `consent:changed`
+  private async syncWithBackend(): Promise<void> {
+    try {
+      const record = await invoke<ConsentRecord>('get_consent');
+      if (this.settings.doNotTrack) {
+        // Do Not Track l'emporte sur un consentement donné auparavant
+        if (Object.values(record.consent).some(Boolean)) {
+          await this.revokeConsent();
+        }
+      } else if (record.updatedAt) {
+        this.applyBackendConsent(record);
+      } else if (Object.values(this.settings.consent).some(Boolean)) {
+        // Migration : consentement donné avant le stockage Rust, poussé une fois par le panel
+        await this.pushConsent();
+      }
+      await listen<ConsentRecord>('consent:changed', ({ payload }) => this.applyBackendConsent(payload));
+    } catch (error) {
+      console.warn('Consentement backend indisponible, localStorage seul:', error);
+    }
+  }
+
+  private applyBackendConsent(record: ConsentRecord): void {
+    if (this.settings.doNotTrack) {
+      return;
+    }
+    this.settings.consent = { ...this.settings.consent, ...record.consent };
+    this.saveSettings();
+  }
+
+  private isSettingsWindow(): boolean {
+    try {
+      return getCurrentWebviewWindow().label === SETTINGS_WINDOW;
+    } catch {
+      return false;
+    }
+  }
+
+  // Enregistrer côté Rust depuis le panel uniquement, les autres fenêtres suivent par événement
+  private async pushConsent(): Promise<void> {
+    if (!this.isSettingsWindow()) {
+      return;
+    }
+    try {
+      await invoke('set_consent', { consent: this.settings.consent });
+    } catch (error) {
+      console.warn('Consentement non enregistré côté backend:', error);
+    }
+  }
+
+  // Retrait de tous les consentements côté Rust, autorisé depuis toutes les fenêtres
+  private revokeConsent(): Promise<void> {
+    if (!this.revoking) {
+      this.revoking = invoke('revoke_consent')
+        .then(() => undefined)
+        .catch((error) => console.warn('Retrait du consentement non enregistré côté backend:', error))
+        .finally(() => {
+          this.revoking = null;
+        });
+    }
+    return this.revoking;
   }
 
   static getInstance(): PrivacyManager {
@@ -39,14 +124,9 @@ export class PrivacyManager {
     if (doNotTrack && this.settings.doNotTrack !== doNotTrack) {
       console.log('Do Not Track détecté - désactivation de tous les consentements');
       this.settings.doNotTrack = true;
-      this.settings.consent = {
-        errorReporting: false,
-        logging: false,
-        metrics: false,
-        analytics: false,
-        marketing: false,
-      };
+      this.settings.consent = { ...NO_CONSENT };
       this.saveSettings();
+      void this.revokeConsent();
     }
   }
 
@@ -139,6 +219,7 @@ export class PrivacyManager {
     };
 
     this.saveSettings();
+    void this.pushConsent();
   }
 
   // Vérifier si un type de consentement est donné
@@ -158,14 +239,9 @@ export class PrivacyManager {
 
   // Réinitialiser tous les consentements
   resetConsent(): void {
-    this.settings.consent = {
-      errorReporting: false,
-      logging: false,
-      metrics: false,
-      analytics: false,
-      marketing: false,
-    };
+    this.settings.consent = { ...NO_CONSENT };
     this.saveSettings();
+    void this.revokeConsent();
   }
 
   // Obtenir les paramètres complets